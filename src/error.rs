@@ -39,6 +39,99 @@ pub enum LotteryError {
     /// Invalid vrf result
     #[error("Invalid random result")]
     InvalidRandomResult,
+    /// Lottery data account is not the expected program address
+    #[error("Invalid lottery account")]
+    InvalidLotteryAccount,
+    /// Lottery authority does not match
+    #[error("Invalid lottery authority")]
+    InvalidAuthority,
+    /// Prize vault is not the lottery vault program address
+    #[error("Invalid prize vault account")]
+    InvalidVaultAccount,
+    /// Lottery status does not allow this operation
+    #[error("Invalid lottery status transition")]
+    InvalidStatusTransition,
+    /// Game matrix does not fit into ticket combination
+    #[error("Invalid game matrix")]
+    InvalidGameMatrix,
+    /// Ticket price is invalid
+    #[error("Invalid ticket price")]
+    InvalidTicketPrice,
+    /// Ticket sales already started
+    #[error("Ticket sales already started")]
+    SalesStarted,
+    /// Price feed account is invalid or has no valid price
+    #[error("Invalid price feed")]
+    InvalidPriceFeed,
+    /// Price feed round is too old
+    #[error("Stale price feed")]
+    StalePriceFeed,
+    /// Arithmetic overflow
+    #[error("Arithmetic overflow")]
+    Overflow,
+    /// Ticket price exceeds the max price user agreed to pay
+    #[error("Ticket price exceeds max price")]
+    PriceExceedsMax,
+    /// Token account does not match lottery token mint or owner
+    #[error("Invalid token account")]
+    InvalidTokenAccount,
+    /// Ticket lines count is zero or exceeds ticket book capacity
+    #[error("Invalid ticket lines count")]
+    InvalidTicketLinesCount,
+    /// Quick pick ticket numbers are not assigned yet
+    #[error("Quick pick numbers are not assigned")]
+    QuickPickNotAssigned,
+    /// Ticket prize is already claimed
+    #[error("Prize already claimed")]
+    AlreadyClaimed,
+    /// Ticket has no winning lines
+    #[error("Ticket has no prize")]
+    NoPrize,
+    /// Not all ticket lines are settled
+    #[error("Settlement is incomplete")]
+    SettlementIncomplete,
+    /// Prize table tiers do not match game matrix or pool shares exceed prize pool
+    #[error("Invalid prize table")]
+    InvalidPrizeTable,
+    /// Randomness account does not match the lottery randomness account
+    #[error("Invalid randomness account")]
+    InvalidRandomnessAccount,
+    /// Randomness result was produced before ticket sales closed
+    #[error("Randomness is not fulfilled after sales close")]
+    StaleRandomness,
+    /// Lottery randomness mode does not allow this operation
+    #[error("Invalid randomness mode")]
+    InvalidRandomnessMode,
+    /// Revealed seed does not match the seed commitment
+    #[error("Invalid seed reveal")]
+    InvalidSeedReveal,
+    /// Hash of the first slot after sales close is not in SlotHashes sysvar
+    #[error("Slot hash after sales close is not available")]
+    SlotHashNotAvailable,
+    /// Sales schedule timestamps are not in order
+    #[error("Invalid sales schedule")]
+    InvalidSalesSchedule,
+    /// Ticket sales window is not open yet
+    #[error("Ticket sales are not open")]
+    SalesNotOpen,
+    /// Ticket sales window is over
+    #[error("Ticket sales are closed")]
+    SalesClosed,
+    /// Draw time is not reached yet
+    #[error("Draw time is not reached")]
+    DrawTooEarly,
+    /// Registrations or prize pool changed since sales close
+    #[error("Sales snapshot mismatch")]
+    SalesSnapshotMismatch,
+    /// Ticket is purchased in another lottery or lottery round
+    #[error("Invalid ticket lottery")]
+    InvalidTicketLottery,
+    /// Seed reveal deadline is not passed yet
+    #[error("Lottery can not be cancelled yet")]
+    CancelTooEarly,
+    /// Sales close time is not reached yet
+    #[error("Sales close time is not reached")]
+    SalesCloseTooEarly,
 }
 
 impl From<LotteryError> for ProgramError {
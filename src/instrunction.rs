@@ -1,10 +1,15 @@
 //! Instruction types
 use crate::error::LotteryError::InvalidInstruction;
-use crate::processor::check_program_account;
+use crate::processor::{
+    chainlink_store, check_program_account, find_lottery_address, find_token_vault_address,
+    find_vault_address, switchboard,
+};
+use crate::state::LotteryConfig;
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     msg,
     program_error::ProgramError,
+    program_pack::Pack,
     pubkey::Pubkey,
     sysvar,
 };
@@ -14,55 +19,102 @@ use std::{convert::TryInto, mem::size_of};
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq)]
 pub enum LotteryInstruction {
-    /// Initialize new lottery data
+    /// Initialize new lottery data with `config`
+    /// Lottery data account is a program address derived from
+    /// `["lottery", lottery authority, lottery_id]` and created by this instruction.
+    /// Ticket is priced in USD by the Chainlink SOL/USD `price_feed`, which must be set,
+    /// if `ticket_price_usd_cents` is not zero, otherwise by `ticket_price_lamports`.
+    /// If `token_mint` is not default pubkey, tickets are paid and prizes rewarded
+    /// in that SPL token and `ticket_price_lamports` is the price in token base units.
+    /// Pool shares of `prize_table` together with sollotto reward must not exceed 100%.
+    /// In `CommitReveal` randomness mode `seed_commitment` is the hash of the operator seed
+    /// revealed by `RevealSeed`, in `Deterministic` test mode it is the draw randomness,
+    /// otherwise it is ignored.
+    /// Tickets are sold from `sales_open_ts` until `sales_close_ts`, winning numbers
+    /// are drawn not earlier than `draw_after_ts`.
     /// Accounts expected by this instruction:
     ///
-    /// 0. `[writable, signer]` Lottery data account
-    /// 1. `[]` Rent sysvar
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[writable, signer]` Lottery authority (pays for lottery data account)
+    /// 2. `[]` Rent sysvar
+    /// 3. `[]` System program account
+    /// 4. `[writable]` Lottery prize vault (program address `["vault", lottery data account]`),
+    ///    funded by the authority with rent exempt balance for SOL lottery
+    /// 5. `[]` SPL token mint, only for SPL token lottery
+    /// 6. `[writable]` Lottery token vault
+    ///    (program address `["token_vault", lottery data account]`), only for SPL token lottery
+    /// 7. `[]` SPL Token program account, only for SPL token lottery
     InitLottery {
         lottery_id: u32,
-        
-        holding_wallet: Pubkey,
-        rewards_wallet: Pubkey,
-        
-        randomness_account: Pubkey,
+        config: LotteryConfig,
     },
 
     /// User purchases new ticket for lottery
+    /// If `quick_pick` is set, `ticket_number_arr` is ignored and ticket numbers
+    /// are assigned by `AssignQuickPicks` from the draw randomness.
     /// Accounts expected by this instruction:
     ///
-    /// 0. `[writable, signer]` Lottery data account
+    /// 0. `[writable]` Lottery data account
     /// 1. `[writable]` Users ticket data account
     /// 2. `[writable,signer]` User funding account (must be a system account)
-    /// 3. `[writable]` Sollotto holding wallet account (must be a system account)
+    /// 3. `[writable]` Lottery prize vault (program address `["vault", lottery data account]`)
     /// 4. `[wirtable]` User's SolLotto Lifetime Ticket Account
     /// 5. `[signer]` SolLotto Lifetime Ticket mint authority
     /// 6. `[writable]` SolLotto Lifetime Ticket Mint
     /// 7. `[]` Rent sysvar
     /// 8. `[]` System program account
     /// 9. `[]` SPL Token program account
+    /// 10. `[]` Clock sysvar
+    /// 11. `[]` Chainlink SOL/USD price feed account, only for USD priced lottery
+    /// 12. `[]` Chainlink store program account, only for USD priced lottery
+    ///
+    /// For SPL token lottery user funding account is the owner of the paying token account
+    /// and accounts 11 - 12 are:
+    /// 11. `[writable]` User token account of the lottery mint
+    /// 12. `[writable]` Lottery token vault
     PurchaseTicket {
         charity: Pubkey,
         user_wallet_pk: Pubkey,
         ticket_number_arr: [u8; 6],
+        /// Max price user agrees to pay for the ticket,
+        /// in lamports or in token base units for SPL token lottery
+        max_price: u64,
+        quick_pick: bool,
     },
 
-    /// Store the winning combination into lottery data account
+    /// Store the winning combination into lottery data account.
+    /// Randomness must be requested by `RequestRandomness`, randomness account round must be
+    /// fulfilled after the request. Moves lottery from `AwaitingRandomness` to `Drawn`.
+    /// Lottery in `Deterministic` test randomness mode is drawn right after `CloseSales`.
     /// Accounts expected by this instruction:
     ///
-    /// 0. `[writable, signer]` Lottery data account
-    /// 1. `[]` Vrf account
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[]` Vrf account, must be the lottery randomness account
+    /// 3. `[]` Clock sysvar
     StoreWinningNumbers {},
 
     /// Check users number combinations and find the lottery winner.
     /// Information obout winner sotored in LotteryResultData account,
+    /// lottery is moved from `Drawn` to `Settled`.
+    /// Prize of one winning line of every tier is stored in lottery data account
+    /// and kept in prize vault until winners claim it by `ClaimPrize`.
     /// Accounts expected by this instruction:
     ///
-    /// 0. `[writable, signer]` Lottery data account
-    /// 1. `[writable]` Lottery result data account
-    /// 2. `[writable, signer]` Sollotto holding wallet account (must be a system account)
-    /// 3. `[writable]` Solloto rewards wallet account (must be a system account)
-    /// 4. + N*2. `[]` N*2 readonly percipients accounts pairs: (ticket_acc, user_wallet_acc (system account))
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[writable]` Lottery result data account
+    /// 3. `[writable]` Lottery prize vault (program address `["vault", lottery data account]`)
+    /// 4. `[writable]` Sollotto holding wallet account, receives undistributed prize pool
+    /// 5. `[writable]` Solloto rewards wallet account (must be a system account)
+    /// 6. `[]` System program account
+    /// 7. + N*2. `[]` N*2 readonly percipients accounts pairs: (ticket_acc, user_wallet_acc (system account))
+    ///    in ticket purchase order
+    ///
+    /// For SPL token lottery wallets 4, 5 and participants wallets are token accounts
+    /// of the lottery mint owned by these wallets, and participants pairs follow:
+    /// 7. `[writable]` Lottery token vault
+    /// 8. `[]` SPL Token program account
     RewardWinners {},
 
    
@@ -70,11 +122,162 @@ pub enum LotteryInstruction {
     /// Update sollotto wallets in lottery data account
     /// Accounts expected by this instruction:
     ///
-    /// 0. `[writable, signer]` Lottery data account
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
     UpdateSollottoWallets {
         holding_wallet: Pubkey,
         rewards_wallet: Pubkey,
     },
+
+    /// Update ticket price in lottery data account,
+    /// allowed only before ticket sales open at `sales_open_ts`.
+    /// `ticket_price_lamports` is in token base units for SPL token lottery,
+    /// USD ticket price requires the lottery price feed
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[]` Clock sysvar
+    UpdateTicketPrice {
+        ticket_price_lamports: u64,
+        ticket_price_usd_cents: u64,
+    },
+
+    /// User purchases several number combinations (lines) into one ticket book,
+    /// paying ticket price for every line.
+    /// Accounts expected by this instruction are the same as for `PurchaseTicket`,
+    /// except account 1 is the `[writable]` Ticket book data account.
+    PurchaseTickets {
+        charity: Pubkey,
+        user_wallet_pk: Pubkey,
+        ticket_number_arrs: Vec<[u8; 6]>,
+        /// Max price user agrees to pay for all lines,
+        /// in lamports or in token base units for SPL token lottery
+        max_price: u64,
+    },
+
+    /// Assign quick pick ticket numbers from the lottery draw randomness,
+    /// allowed to anyone after the winning numbers are stored.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` Lottery data account
+    /// 1. + N. `[writable]` N quick pick ticket data accounts
+    AssignQuickPicks {},
+
+    /// Pay prize of all winning lines of the ticket to its user wallet
+    /// and mark the ticket claimed, allowed to anyone after lottery is settled.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[writable]` Ticket or ticket book data account
+    /// 2. `[writable]` Lottery prize vault
+    /// 3. `[writable]` User wallet of the ticket, token account of the lottery mint
+    ///    owned by the user wallet for SPL token lottery
+    /// 4. `[]` System program account
+    /// 5. `[writable]` Lottery token vault, only for SPL token lottery
+    /// 6. `[]` SPL Token program account, only for SPL token lottery
+    ClaimPrize {},
+
+    /// Count winning lines of the next slice of tickets, tickets must follow in purchase order.
+    /// Lottery is moved from `Drawn` to `Settling` by the first batch.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. + N. `[]` N ticket or ticket book data accounts
+    SettleBatch {},
+
+    /// Compute prize of every tier from winners counted by `SettleBatch`
+    /// once all tickets are settled, lottery is moved from `Settling` to `Settled`.
+    /// Accounts expected by this instruction are the same as for `RewardWinners`
+    /// without participants accounts.
+    FinalizeSettlement {},
+
+    /// Move dust of the settled lottery prize pool into prize pool of the next lottery
+    /// of the same authority and token mint.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Settled lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[writable]` Settled lottery prize vault
+    /// 3. `[writable]` Next lottery data account
+    /// 4. `[writable]` Next lottery prize vault
+    /// 5. `[]` System program account
+    /// 6. `[writable]` Settled lottery token vault, only for SPL token lottery
+    /// 7. `[writable]` Next lottery token vault, only for SPL token lottery
+    /// 8. `[]` SPL Token program account, only for SPL token lottery
+    RolloverPrizePool {},
+
+    /// Close ticket sales and capture the randomness account round counter,
+    /// moves lottery from `Open` to `SalesClosed`.
+    /// Freezes total registrations and prize pool, settlement requires them unchanged.
+    /// `CommitReveal` lottery sales close not earlier than `draw_after_ts`, so the seed
+    /// can be revealed while the first slot after sales close is in SlotHashes sysvar.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[]` Clock sysvar
+    /// 3. `[]` Vrf account, must be the lottery randomness account,
+    ///    only for `SwitchboardVrf` randomness mode
+    CloseSales {},
+
+    /// Request a fresh VRF for the lottery randomness account from the Switchboard program,
+    /// moves lottery from `SalesClosed` to `AwaitingRandomness`.
+    /// Lottery data account signs the request as the Vrf account authority,
+    /// lottery authority pays the request fee from the payer wallet.
+    /// Accounts 5 - 13 are passed to the Switchboard `vrf_request_randomness` instruction.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[writable]` Vrf account, must be the lottery randomness account
+    /// 3. `[]` Switchboard program account
+    /// 4. `[]` Clock sysvar
+    /// 5. `[writable]` Oracle queue of the Vrf account
+    /// 6. `[]` Oracle queue authority
+    /// 7. `[]` Oracle queue data buffer
+    /// 8. `[writable]` Vrf account permission on the oracle queue
+    /// 9. `[writable]` Vrf account escrow token account
+    /// 10. `[writable]` Payer token account of the lottery authority
+    /// 11. `[]` RecentBlockhashes sysvar
+    /// 12. `[]` Switchboard program state account
+    /// 13. `[]` SPL Token program account
+    RequestRandomness {
+        /// Bump seed of the Vrf account permission
+        permission_bump_seed: u8,
+        /// Bump seed of the Switchboard program state
+        state_bump_seed: u8,
+    },
+
+    /// Reveal the operator seed of `CommitReveal` lottery and store the winning combination
+    /// derived from the seed and the hash of the first slot after sales close.
+    /// Moves lottery from `SalesClosed` to `Drawn`.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[signer]` Lottery authority
+    /// 2. `[]` SlotHashes sysvar
+    /// 3. `[]` Clock sysvar
+    RevealSeed { seed: [u8; 32] },
+
+    /// Cancel `CommitReveal` lottery whose seed was not revealed while the hash of the first
+    /// slot after sales close was in SlotHashes sysvar, allowed to anyone.
+    /// Moves lottery from `SalesClosed` to `Cancelled`.
+    /// `SwitchboardVrf` lottery not drawn within `DRAW_TIMEOUT_SECS` after draw time
+    /// is cancelled from `Open`, `SalesClosed` or `AwaitingRandomness`, requested randomness
+    /// must also be unfulfilled for `VRF_FULFILLMENT_TIMEOUT_SLOTS` after the request.
+    /// Tickets of cancelled lottery are refunded by `RefundTicket`.
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Lottery data account
+    /// 1. `[]` Clock sysvar
+    CancelLottery {},
+
+    /// Pay back the price of the ticket of the cancelled lottery to its user wallet
+    /// and mark the ticket claimed, allowed to anyone.
+    /// Accounts expected by this instruction are the same as for `ClaimPrize`.
+    RefundTicket {},
 }
 
 impl LotteryInstruction {
@@ -88,32 +291,28 @@ impl LotteryInstruction {
                     .ok()
                     .map(u32::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
+                let (config, _) = Self::unpack_lottery_config(rest)?;
 
-               
-                let (holding_wallet, rest) = Self::unpack_pubkey(rest).unwrap();
-                let (rewards_wallet, rest) = Self::unpack_pubkey(rest).unwrap();
-                
-                let (randomness_account, _) = Self::unpack_pubkey(rest).unwrap();
-
-                Self::InitLottery {
-                    lottery_id,
-                  
-                    holding_wallet,
-                    rewards_wallet,
-                    
-                    randomness_account,
-                }
+                Self::InitLottery { lottery_id, config }
             }
 
             1 => {
-               
+                let (charity, rest) = Self::unpack_pubkey(rest).unwrap();
                 let (user_wallet_pk, rest) = Self::unpack_pubkey(rest).unwrap();
-                let (ticket_number_arr, _) = Self::unpack_ticket_number_arr(rest).unwrap();
+                let (ticket_number_arr, rest) = Self::unpack_ticket_number_arr(rest).unwrap();
+                let (max_price, rest) = Self::unpack_u64(rest)?;
+                let quick_pick = match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
 
                 Self::PurchaseTicket {
-                   
+                    charity,
                     user_wallet_pk,
                     ticket_number_arr: *ticket_number_arr,
+                    max_price,
+                    quick_pick,
                 }
             }
 
@@ -125,16 +324,74 @@ impl LotteryInstruction {
 
             4 => {
                 let (holding_wallet, rest) = Self::unpack_pubkey(rest).unwrap();
-                let (rewards_wallet, rest) = Self::unpack_pubkey(rest).unwrap();
-               
+                let (rewards_wallet, _) = Self::unpack_pubkey(rest).unwrap();
 
                 Self::UpdateSollottoWallets {
                     holding_wallet,
                     rewards_wallet,
-                   
                 }
             }
 
+            5 => {
+                let (ticket_price_lamports, rest) = Self::unpack_u64(rest)?;
+                let (ticket_price_usd_cents, _) = Self::unpack_u64(rest)?;
+                Self::UpdateTicketPrice {
+                    ticket_price_lamports,
+                    ticket_price_usd_cents,
+                }
+            }
+
+            6 => {
+                let (charity, rest) = Self::unpack_pubkey(rest)?;
+                let (user_wallet_pk, rest) = Self::unpack_pubkey(rest)?;
+                let (max_price, rest) = Self::unpack_u64(rest)?;
+                let (lines_count, mut rest) = rest.split_first().ok_or(InvalidInstruction)?;
+                let mut ticket_number_arrs = Vec::with_capacity(*lines_count as usize);
+                for _ in 0..*lines_count {
+                    let (ticket_number_arr, next) = Self::unpack_ticket_number_arr(rest)?;
+                    ticket_number_arrs.push(*ticket_number_arr);
+                    rest = next;
+                }
+
+                Self::PurchaseTickets {
+                    charity,
+                    user_wallet_pk,
+                    ticket_number_arrs,
+                    max_price,
+                }
+            }
+
+            7 => Self::AssignQuickPicks {},
+
+            8 => Self::ClaimPrize {},
+
+            9 => Self::SettleBatch {},
+
+            10 => Self::FinalizeSettlement {},
+
+            11 => Self::RolloverPrizePool {},
+
+            12 => Self::CloseSales {},
+
+            13 => {
+                let (&permission_bump_seed, rest) =
+                    rest.split_first().ok_or(InvalidInstruction)?;
+                let (&state_bump_seed, _) = rest.split_first().ok_or(InvalidInstruction)?;
+                Self::RequestRandomness {
+                    permission_bump_seed,
+                    state_bump_seed,
+                }
+            }
+
+            14 => {
+                let (seed, _) = Self::unpack_hash(rest)?;
+                Self::RevealSeed { seed }
+            }
+
+            15 => Self::CancelLottery {},
+
+            16 => Self::RefundTicket {},
+
             _ => return Err(InvalidInstruction.into()),
         })
     }
@@ -143,32 +400,27 @@ impl LotteryInstruction {
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
         match self {
-            Self::InitLottery {
-                lottery_id,
-                
-                holding_wallet,
-                rewards_wallet,
-                
-                randomness_account,
-            } => {
+            Self::InitLottery { lottery_id, config } => {
                 buf.push(0);
                 buf.extend_from_slice(&lottery_id.to_le_bytes());
-               
-                buf.extend_from_slice(holding_wallet.as_ref());
-                buf.extend_from_slice(rewards_wallet.as_ref());
-               
-                buf.extend_from_slice(randomness_account.as_ref());
+                let mut config_buf = [0u8; LotteryConfig::LEN];
+                config.pack_into_slice(&mut config_buf);
+                buf.extend_from_slice(&config_buf);
             }
 
             Self::PurchaseTicket {
                 charity,
                 user_wallet_pk,
                 ticket_number_arr,
+                max_price,
+                quick_pick,
             } => {
                 buf.push(1);
-               
+                buf.extend_from_slice(charity.as_ref());
                 buf.extend_from_slice(user_wallet_pk.as_ref());
                 buf.extend_from_slice(&ticket_number_arr.as_ref());
+                buf.extend_from_slice(&max_price.to_le_bytes());
+                buf.push(*quick_pick as u8);
             }
 
             Self::StoreWinningNumbers {} => {
@@ -184,14 +436,83 @@ impl LotteryInstruction {
             Self::UpdateSollottoWallets {
                 holding_wallet,
                 rewards_wallet,
-                slot_holders_rewards_wallet,
-                sollotto_labs_wallet,
             } => {
                 buf.push(4);
                 buf.extend_from_slice(holding_wallet.as_ref());
                 buf.extend_from_slice(rewards_wallet.as_ref());
                 
             }
+
+            Self::UpdateTicketPrice {
+                ticket_price_lamports,
+                ticket_price_usd_cents,
+            } => {
+                buf.push(5);
+                buf.extend_from_slice(&ticket_price_lamports.to_le_bytes());
+                buf.extend_from_slice(&ticket_price_usd_cents.to_le_bytes());
+            }
+
+            Self::PurchaseTickets {
+                charity,
+                user_wallet_pk,
+                ticket_number_arrs,
+                max_price,
+            } => {
+                buf.push(6);
+                buf.extend_from_slice(charity.as_ref());
+                buf.extend_from_slice(user_wallet_pk.as_ref());
+                buf.extend_from_slice(&max_price.to_le_bytes());
+                buf.push(ticket_number_arrs.len() as u8);
+                for ticket_number_arr in ticket_number_arrs {
+                    buf.extend_from_slice(ticket_number_arr.as_ref());
+                }
+            }
+
+            Self::AssignQuickPicks {} => {
+                buf.push(7);
+            }
+
+            Self::ClaimPrize {} => {
+                buf.push(8);
+            }
+
+            Self::SettleBatch {} => {
+                buf.push(9);
+            }
+
+            Self::FinalizeSettlement {} => {
+                buf.push(10);
+            }
+
+            Self::RolloverPrizePool {} => {
+                buf.push(11);
+            }
+
+            Self::CloseSales {} => {
+                buf.push(12);
+            }
+
+            Self::RequestRandomness {
+                permission_bump_seed,
+                state_bump_seed,
+            } => {
+                buf.push(13);
+                buf.push(*permission_bump_seed);
+                buf.push(*state_bump_seed);
+            }
+
+            Self::RevealSeed { seed } => {
+                buf.push(14);
+                buf.extend_from_slice(seed);
+            }
+
+            Self::CancelLottery {} => {
+                buf.push(15);
+            }
+
+            Self::RefundTicket {} => {
+                buf.push(16);
+            }
         };
         buf
     }
@@ -206,6 +527,38 @@ impl LotteryInstruction {
         Ok((pk, rest))
     }
 
+    fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+        if input.len() < 8 {
+            msg!("u64 cannot be unpacked");
+            return Err(InvalidInstruction.into());
+        }
+        let (bytes, rest) = input.split_at(8);
+        let value = bytes
+            .try_into()
+            .ok()
+            .map(u64::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+        Ok((value, rest))
+    }
+
+    fn unpack_hash(input: &[u8]) -> Result<([u8; 32], &[u8]), ProgramError> {
+        if input.len() < 32 {
+            msg!("Hash cannot be unpacked");
+            return Err(InvalidInstruction.into());
+        }
+        let (bytes, rest) = input.split_at(32);
+        Ok((bytes.try_into().map_err(|_| InvalidInstruction)?, rest))
+    }
+
+    fn unpack_lottery_config(input: &[u8]) -> Result<(LotteryConfig, &[u8]), ProgramError> {
+        if input.len() < LotteryConfig::LEN {
+            msg!("Lottery config cannot be unpacked");
+            return Err(InvalidInstruction.into());
+        }
+        let (bytes, rest) = input.split_at(LotteryConfig::LEN);
+        Ok((LotteryConfig::unpack_from_slice(bytes)?, rest))
+    }
+
     fn unpack_ticket_number_arr(input: &[u8]) -> Result<(&[u8; 6], &[u8]), ProgramError> {
         if input.len() < 6 {
             msg!("Cannot be unpacked");
@@ -216,31 +569,40 @@ impl LotteryInstruction {
     }
 }
 
-/// Creates a `InitLottery` instruction
+/// Creates a `InitLottery` instruction,
+/// config `ticket_price_lamports` is in token base units for SPL token lottery
 pub fn initialize_lottery(
     program_id: &Pubkey,
     lottery_id: u32,
-    
-    holding_wallet: &Pubkey,
-    rewards_wallet: &Pubkey,
-   
-    randomness_account: &Pubkey,
+    config: &LotteryConfig,
     lottery_authority: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(program_id)?;
     let data = LotteryInstruction::InitLottery {
-        lottery_id: lottery_id,
-       
-        holding_wallet: *holding_wallet,
-        rewards_wallet: *rewards_wallet,
-        
-        randomness_account: *randomness_account,
+        lottery_id,
+        config: *config,
     }
     .pack();
 
-    let mut accounts = Vec::with_capacity(2);
+    let (lottery_key, _) = find_lottery_address(program_id, lottery_authority, lottery_id);
+
+    let (vault_key, _) = find_vault_address(program_id, &lottery_key);
+
+    let mut accounts = Vec::with_capacity(8);
+    accounts.push(AccountMeta::new(lottery_key, false));
     accounts.push(AccountMeta::new(*lottery_authority, true));
     accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        solana_program::system_program::id(),
+        false,
+    ));
+    accounts.push(AccountMeta::new(vault_key, false));
+    if config.token_mint != Pubkey::default() {
+        let (token_vault_key, _) = find_token_vault_address(program_id, &lottery_key);
+        accounts.push(AccountMeta::new_readonly(config.token_mint, false));
+        accounts.push(AccountMeta::new(token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
@@ -249,32 +611,111 @@ pub fn initialize_lottery(
     })
 }
 
-/// Creates a `PurchaseTicket` instruction
+/// Creates a `PurchaseTicket` instruction,
+/// `max_price` is in token base units for SPL token lottery
 pub fn purchase_ticket(
     program_id: &Pubkey,
-    
+    charity: &Pubkey,
     user_wallet_pk: &Pubkey,
     ticket_number_arr: &[u8; 6],
+    max_price: u64,
+    quick_pick: bool,
     user_ticket_key: &Pubkey,
-    holding_wallet_key: &Pubkey,
-    lottery_authority: &Pubkey,
+    lottery_key: &Pubkey,
     user_lifetime_ticket_account: &Pubkey,
     lifetime_ticket_owner: &Pubkey,
     lifetime_ticket_mint: &Pubkey,
+    price_feed: Option<&Pubkey>,
+    user_token_account: Option<&Pubkey>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(program_id)?;
     let data = LotteryInstruction::PurchaseTicket {
-      
+        charity: *charity,
         user_wallet_pk: *user_wallet_pk,
         ticket_number_arr: *ticket_number_arr,
+        max_price,
+        quick_pick,
     }
     .pack();
 
-    let mut accounts = Vec::with_capacity(9);
-    accounts.push(AccountMeta::new(*lottery_authority, true));
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: purchase_accounts(
+            program_id,
+            user_wallet_pk,
+            user_ticket_key,
+            lottery_key,
+            user_lifetime_ticket_account,
+            lifetime_ticket_owner,
+            lifetime_ticket_mint,
+            price_feed,
+            user_token_account,
+        ),
+        data,
+    })
+}
+
+/// Creates a `PurchaseTickets` instruction,
+/// `max_price` is in token base units for SPL token lottery
+pub fn purchase_tickets(
+    program_id: &Pubkey,
+    charity: &Pubkey,
+    user_wallet_pk: &Pubkey,
+    ticket_number_arrs: &[[u8; 6]],
+    max_price: u64,
+    user_ticket_book_key: &Pubkey,
+    lottery_key: &Pubkey,
+    user_lifetime_ticket_account: &Pubkey,
+    lifetime_ticket_owner: &Pubkey,
+    lifetime_ticket_mint: &Pubkey,
+    price_feed: Option<&Pubkey>,
+    user_token_account: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::PurchaseTickets {
+        charity: *charity,
+        user_wallet_pk: *user_wallet_pk,
+        ticket_number_arrs: ticket_number_arrs.to_vec(),
+        max_price,
+    }
+    .pack();
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: purchase_accounts(
+            program_id,
+            user_wallet_pk,
+            user_ticket_book_key,
+            lottery_key,
+            user_lifetime_ticket_account,
+            lifetime_ticket_owner,
+            lifetime_ticket_mint,
+            price_feed,
+            user_token_account,
+        ),
+        data,
+    })
+}
+
+/// Accounts of `PurchaseTicket` and `PurchaseTickets` instructions
+fn purchase_accounts(
+    program_id: &Pubkey,
+    user_wallet_pk: &Pubkey,
+    user_ticket_key: &Pubkey,
+    lottery_key: &Pubkey,
+    user_lifetime_ticket_account: &Pubkey,
+    lifetime_ticket_owner: &Pubkey,
+    lifetime_ticket_mint: &Pubkey,
+    price_feed: Option<&Pubkey>,
+    user_token_account: Option<&Pubkey>,
+) -> Vec<AccountMeta> {
+    let (vault_key, _) = find_vault_address(program_id, lottery_key);
+
+    let mut accounts = Vec::with_capacity(13);
+    accounts.push(AccountMeta::new(*lottery_key, false));
     accounts.push(AccountMeta::new(*user_ticket_key, false));
     accounts.push(AccountMeta::new(*user_wallet_pk, true));
-    accounts.push(AccountMeta::new(*holding_wallet_key, false));
+    accounts.push(AccountMeta::new(vault_key, false));
     accounts.push(AccountMeta::new(*user_lifetime_ticket_account, false));
     accounts.push(AccountMeta::new_readonly(*lifetime_ticket_owner, true));
     accounts.push(AccountMeta::new(*lifetime_ticket_mint, false));
@@ -284,25 +725,34 @@ pub fn purchase_ticket(
         false,
     ));
     accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
-
-    Ok(Instruction {
-        program_id: *program_id,
-        accounts,
-        data,
-    })
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    if let Some(price_feed) = price_feed {
+        accounts.push(AccountMeta::new_readonly(*price_feed, false));
+        accounts.push(AccountMeta::new_readonly(chainlink_store::id(), false));
+    }
+    if let Some(user_token_account) = user_token_account {
+        let (token_vault_key, _) = find_token_vault_address(program_id, lottery_key);
+        accounts.push(AccountMeta::new(*user_token_account, false));
+        accounts.push(AccountMeta::new(token_vault_key, false));
+    }
+    accounts
 }
 
 /// Creates a `StoreWinningNumbers` instruction
 pub fn store_winning_numbers(
     program_id: &Pubkey,
-    winning_numbers_arr: &[u8; 6],
+    lottery_key: &Pubkey,
     lottery_authority: &Pubkey,
+    vrf_account: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(program_id)?;
     let data = LotteryInstruction::StoreWinningNumbers {}.pack();
 
-    let mut accounts = Vec::with_capacity(1);
-    accounts.push(AccountMeta::new(*lottery_authority, true));
+    let mut accounts = Vec::with_capacity(4);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new_readonly(*vrf_account, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
 
     Ok(Instruction {
         program_id: *program_id,
@@ -314,25 +764,35 @@ pub fn store_winning_numbers(
 /// Creates a `RewardWinners` instruction
 pub fn reward_winners(
     program_id: &Pubkey,
+    lottery_key: &Pubkey,
     lottery_authority: &Pubkey,
     lottery_result: &Pubkey,
     holding_wallet: &Pubkey,
     rewards_wallet: &Pubkey,
+    token_lottery: bool,
     participants: &Vec<(Pubkey, Pubkey)>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(program_id)?;
     let data = LotteryInstruction::RewardWinners {}.pack();
 
-    let mut accounts = Vec::with_capacity(9 + participants.len());
-    accounts.push(AccountMeta::new(*lottery_authority, true));
+    let (vault_key, _) = find_vault_address(program_id, lottery_key);
+
+    let mut accounts = Vec::with_capacity(9 + participants.len() * 2);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
     accounts.push(AccountMeta::new(*lottery_result, false));
-    accounts.push(AccountMeta::new(*holding_wallet, true));
+    accounts.push(AccountMeta::new(vault_key, false));
+    accounts.push(AccountMeta::new(*holding_wallet, false));
     accounts.push(AccountMeta::new(*rewards_wallet, false));
-   
     accounts.push(AccountMeta::new_readonly(
         solana_program::system_program::id(),
         false,
     ));
+    if token_lottery {
+        let (token_vault_key, _) = find_token_vault_address(program_id, lottery_key);
+        accounts.push(AccountMeta::new(token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
     for participant in participants {
         accounts.push(AccountMeta::new_readonly(participant.0, false));
         accounts.push(AccountMeta::new(participant.1, false));
@@ -345,24 +805,354 @@ pub fn reward_winners(
     })
 }
 
-
 /// Creates a `UpdateSollottoWallets` instruction
 pub fn update_sollotto_wallets(
     program_id: &Pubkey,
     holding_wallet: &Pubkey,
     rewards_wallet: &Pubkey,
+    lottery_key: &Pubkey,
     lottery_authority: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(program_id)?;
     let data = LotteryInstruction::UpdateSollottoWallets {
         holding_wallet: *holding_wallet,
         rewards_wallet: *rewards_wallet,
-        
     }
     .pack();
 
-    let mut accounts = Vec::with_capacity(1);
-    accounts.push(AccountMeta::new(*lottery_authority, true));
+    let mut accounts = Vec::with_capacity(2);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `UpdateTicketPrice` instruction,
+/// `ticket_price_lamports` is in token base units for SPL token lottery
+pub fn update_ticket_price(
+    program_id: &Pubkey,
+    ticket_price_lamports: u64,
+    ticket_price_usd_cents: u64,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::UpdateTicketPrice {
+        ticket_price_lamports,
+        ticket_price_usd_cents,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(3);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `AssignQuickPicks` instruction
+pub fn assign_quick_picks(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    tickets: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::AssignQuickPicks {}.pack();
+
+    let mut accounts = Vec::with_capacity(1 + tickets.len());
+    accounts.push(AccountMeta::new_readonly(*lottery_key, false));
+    for ticket in tickets {
+        accounts.push(AccountMeta::new(*ticket, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ClaimPrize` instruction
+pub fn claim_prize(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    ticket_key: &Pubkey,
+    user_wallet: &Pubkey,
+    token_lottery: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::ClaimPrize {}.pack();
+
+    let (vault_key, _) = find_vault_address(program_id, lottery_key);
+
+    let mut accounts = Vec::with_capacity(7);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new(*ticket_key, false));
+    accounts.push(AccountMeta::new(vault_key, false));
+    accounts.push(AccountMeta::new(*user_wallet, false));
+    accounts.push(AccountMeta::new_readonly(
+        solana_program::system_program::id(),
+        false,
+    ));
+    if token_lottery {
+        let (token_vault_key, _) = find_token_vault_address(program_id, lottery_key);
+        accounts.push(AccountMeta::new(token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SettleBatch` instruction
+pub fn settle_batch(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    tickets: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::SettleBatch {}.pack();
+
+    let mut accounts = Vec::with_capacity(2 + tickets.len());
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    for ticket in tickets {
+        accounts.push(AccountMeta::new_readonly(*ticket, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FinalizeSettlement` instruction
+pub fn finalize_settlement(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    lottery_result: &Pubkey,
+    holding_wallet: &Pubkey,
+    rewards_wallet: &Pubkey,
+    token_lottery: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::FinalizeSettlement {}.pack();
+
+    let (vault_key, _) = find_vault_address(program_id, lottery_key);
+
+    let mut accounts = Vec::with_capacity(9);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new(*lottery_result, false));
+    accounts.push(AccountMeta::new(vault_key, false));
+    accounts.push(AccountMeta::new(*holding_wallet, false));
+    accounts.push(AccountMeta::new(*rewards_wallet, false));
+    accounts.push(AccountMeta::new_readonly(
+        solana_program::system_program::id(),
+        false,
+    ));
+    if token_lottery {
+        let (token_vault_key, _) = find_token_vault_address(program_id, lottery_key);
+        accounts.push(AccountMeta::new(token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RolloverPrizePool` instruction
+pub fn rollover_prize_pool(
+    program_id: &Pubkey,
+    settled_lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    next_lottery_key: &Pubkey,
+    token_lottery: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::RolloverPrizePool {}.pack();
+
+    let (settled_vault_key, _) = find_vault_address(program_id, settled_lottery_key);
+    let (next_vault_key, _) = find_vault_address(program_id, next_lottery_key);
+
+    let mut accounts = Vec::with_capacity(9);
+    accounts.push(AccountMeta::new(*settled_lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new(settled_vault_key, false));
+    accounts.push(AccountMeta::new(*next_lottery_key, false));
+    accounts.push(AccountMeta::new(next_vault_key, false));
+    accounts.push(AccountMeta::new_readonly(
+        solana_program::system_program::id(),
+        false,
+    ));
+    if token_lottery {
+        let (settled_token_vault_key, _) =
+            find_token_vault_address(program_id, settled_lottery_key);
+        let (next_token_vault_key, _) = find_token_vault_address(program_id, next_lottery_key);
+        accounts.push(AccountMeta::new(settled_token_vault_key, false));
+        accounts.push(AccountMeta::new(next_token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseSales` instruction
+pub fn close_sales(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    vrf_account: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::CloseSales {}.pack();
+
+    let mut accounts = Vec::with_capacity(4);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    if let Some(vrf_account) = vrf_account {
+        accounts.push(AccountMeta::new_readonly(*vrf_account, false));
+    }
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RequestRandomness` instruction
+pub fn request_randomness(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    vrf_account: &Pubkey,
+    request_accounts: &switchboard::VrfRequestAccounts,
+    permission_bump_seed: u8,
+    state_bump_seed: u8,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::RequestRandomness {
+        permission_bump_seed,
+        state_bump_seed,
+    }
+    .pack();
+
+    let mut accounts = Vec::with_capacity(14);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new(*vrf_account, false));
+    accounts.push(AccountMeta::new_readonly(switchboard::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+    accounts.push(AccountMeta::new(request_accounts.oracle_queue, false));
+    accounts.push(AccountMeta::new_readonly(request_accounts.queue_authority, false));
+    accounts.push(AccountMeta::new_readonly(request_accounts.data_buffer, false));
+    accounts.push(AccountMeta::new(request_accounts.permission, false));
+    accounts.push(AccountMeta::new(request_accounts.escrow, false));
+    accounts.push(AccountMeta::new(request_accounts.payer_wallet, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::recent_blockhashes::id(), false));
+    accounts.push(AccountMeta::new_readonly(request_accounts.program_state, false));
+    accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RevealSeed` instruction
+pub fn reveal_seed(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    lottery_authority: &Pubkey,
+    seed: &[u8; 32],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::RevealSeed { seed: *seed }.pack();
+
+    let mut accounts = Vec::with_capacity(4);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(*lottery_authority, true));
+    accounts.push(AccountMeta::new_readonly(sysvar::slot_hashes::id(), false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CancelLottery` instruction
+pub fn cancel_lottery(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::CancelLottery {}.pack();
+
+    let mut accounts = Vec::with_capacity(2);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::clock::id(), false));
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RefundTicket` instruction
+pub fn refund_ticket(
+    program_id: &Pubkey,
+    lottery_key: &Pubkey,
+    ticket_key: &Pubkey,
+    user_wallet: &Pubkey,
+    token_lottery: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(program_id)?;
+    let data = LotteryInstruction::RefundTicket {}.pack();
+
+    let (vault_key, _) = find_vault_address(program_id, lottery_key);
+
+    let mut accounts = Vec::with_capacity(7);
+    accounts.push(AccountMeta::new(*lottery_key, false));
+    accounts.push(AccountMeta::new(*ticket_key, false));
+    accounts.push(AccountMeta::new(vault_key, false));
+    accounts.push(AccountMeta::new(*user_wallet, false));
+    accounts.push(AccountMeta::new_readonly(
+        solana_program::system_program::id(),
+        false,
+    ));
+    if token_lottery {
+        let (token_vault_key, _) = find_token_vault_address(program_id, lottery_key);
+        accounts.push(AccountMeta::new(token_vault_key, false));
+        accounts.push(AccountMeta::new_readonly(spl_token::id(), false));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
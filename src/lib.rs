@@ -3,4 +3,5 @@ mod entrypoint;
 pub mod error;
 pub mod instruction;
 pub mod processor;
+pub mod randomness;
 pub mod state;
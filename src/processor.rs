@@ -4,24 +4,36 @@ use std::collections::HashMap;
 use crate::{
     error::LotteryError,
     instruction::LotteryInstruction,
-    state::{LotteryData, LotteryResultData, TicketData},
+    randomness::{vrf_round_counter, CommitReveal, RandomnessProvider, SwitchboardVrf},
+    state::{
+        DrawProof, GameKind, GameMatrix, LotteryConfig, LotteryData, LotteryResultData,
+        LotteryStatus, PrizeKind, PrizeTable, RandomnessMode, TicketBookData, TicketData,
+        BPS_DENOMINATOR, MAX_PRIZE_TIERS, MAX_TICKET_BOOK_LINES,
+    },
 };
+#[cfg(feature = "test-randomness")]
+use crate::randomness::Deterministic;
+use chainlink_solana as chainlink;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::Clock,
     entrypoint::ProgramResult,
+    hash::{hash, hashv},
     msg,
-    native_token::{lamports_to_sol, sol_to_lamports},
-    program::invoke,
+    native_token::LAMPORTS_PER_SOL,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
+    slot_hashes,
     system_instruction,
     sysvar::Sysvar,
 };
-use spl_token::{state::Mint, ui_amount_to_amount};
-
-use switchboard_program::VrfAccount;
+use spl_token::{
+    state::{Account as TokenAccount, Mint},
+    ui_amount_to_amount,
+};
 
 // Sollotto program_id      Add here
 solana_program::declare_id!(" ");
@@ -34,6 +46,373 @@ pub fn check_program_account(program_id: &Pubkey) -> ProgramResult {
     Ok(())
 }
 
+/// Seed prefix of the lottery data account program address
+pub const LOTTERY_SEED: &[u8] = b"lottery";
+
+/// Finds the lottery data account address for the given authority and lottery id
+pub fn find_lottery_address(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    lottery_id: u32,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LOTTERY_SEED, authority.as_ref(), &lottery_id.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// Seed prefix of the lottery prize vault program address
+pub const VAULT_SEED: &[u8] = b"vault";
+
+/// Finds the prize vault address of the given lottery data account
+pub fn find_vault_address(program_id: &Pubkey, lottery_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, lottery_key.as_ref()], program_id)
+}
+
+/// Seed prefix of the lottery token vault program address
+pub const TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+
+/// Finds the token vault address of the given lottery data account.
+/// Token vault is owned by the lottery prize vault.
+pub fn find_token_vault_address(program_id: &Pubkey, lottery_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TOKEN_VAULT_SEED, lottery_key.as_ref()], program_id)
+}
+
+/// Chainlink store program, owner of the price feed accounts
+pub mod chainlink_store {
+    solana_program::declare_id!("HEvSKofvBgfaexv23kMabbYqxasxU3mQ4ibBMEmJWHny");
+}
+
+/// Switchboard program, owner of the VRF accounts
+pub mod switchboard {
+    use solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        sysvar,
+    };
+
+    solana_program::declare_id!("DtmE9D2CSB4L5D6A15mraeEjrGMm6auWVzgaD8hK2tZM");
+
+    /// Instruction discriminator of Switchboard `vrf_request_randomness`,
+    /// first 8 bytes of `sha256("global:vrf_request_randomness")`
+    pub const REQUEST_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [230, 121, 14, 164, 28, 222, 117, 118];
+
+    /// Accounts of the Switchboard VRF request besides the Vrf account, its authority
+    /// and the payer authority
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct VrfRequestAccounts {
+        /// Oracle queue of the Vrf account
+        pub oracle_queue: Pubkey,
+        /// Authority of the oracle queue
+        pub queue_authority: Pubkey,
+        /// Data buffer of the oracle queue
+        pub data_buffer: Pubkey,
+        /// Permission of the Vrf account on the oracle queue
+        pub permission: Pubkey,
+        /// Escrow token account of the Vrf account
+        pub escrow: Pubkey,
+        /// Token account the request fee is paid from
+        pub payer_wallet: Pubkey,
+        /// Switchboard program state
+        pub program_state: Pubkey,
+    }
+
+    /// Creates a Switchboard `vrf_request_randomness` instruction of a new round of
+    /// `vrf_account` signed by `vrf_authority`, the fee is paid from the payer wallet
+    /// of `payer_authority`. Accounts are in the order of the Switchboard instruction:
+    /// authority, vrf, oracle queue, queue authority, data buffer, permission, escrow,
+    /// payer wallet, payer authority, recent blockhashes, program state, token program.
+    /// Data is the discriminator followed by permission and program state bump seeds
+    pub fn request_randomness(
+        vrf_account: &Pubkey,
+        vrf_authority: &Pubkey,
+        payer_authority: &Pubkey,
+        request_accounts: &VrfRequestAccounts,
+        permission_bump_seed: u8,
+        state_bump_seed: u8,
+    ) -> Instruction {
+        let mut data = REQUEST_RANDOMNESS_DISCRIMINATOR.to_vec();
+        data.push(permission_bump_seed);
+        data.push(state_bump_seed);
+        Instruction {
+            program_id: id(),
+            accounts: vec![
+                AccountMeta::new_readonly(*vrf_authority, true),
+                AccountMeta::new(*vrf_account, false),
+                AccountMeta::new(request_accounts.oracle_queue, false),
+                AccountMeta::new_readonly(request_accounts.queue_authority, false),
+                AccountMeta::new_readonly(request_accounts.data_buffer, false),
+                AccountMeta::new(request_accounts.permission, false),
+                AccountMeta::new(request_accounts.escrow, false),
+                AccountMeta::new(request_accounts.payer_wallet, false),
+                AccountMeta::new_readonly(*payer_authority, true),
+                AccountMeta::new_readonly(sysvar::recent_blockhashes::id(), false),
+                AccountMeta::new_readonly(request_accounts.program_state, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data,
+        }
+    }
+}
+
+/// Max age of the Chainlink price feed round accepted for ticket pricing
+pub const MAX_PRICE_FEED_AGE_SECS: i64 = 300;
+
+/// Converts USD cents into lamports by SOL/USD `price` with `decimals`, rounding up
+pub fn usd_cents_to_lamports(usd_cents: u64, price: u128, decimals: u8) -> Option<u64> {
+    if price == 0 {
+        return None;
+    }
+    let numerator = (usd_cents as u128)
+        .checked_mul(LAMPORTS_PER_SOL as u128)?
+        .checked_mul(10u128.checked_pow(decimals as u32)?)?;
+    let denominator = price.checked_mul(100)?;
+    let lamports = numerator
+        .checked_add(denominator - 1)?
+        .checked_div(denominator)?;
+    if lamports > u64::MAX as u128 {
+        return None;
+    }
+    Some(lamports as u64)
+}
+
+/// Sollotto reward share of the prize pool in basis points
+pub const SOLLOTTO_REWARD_BPS: u64 = 500;
+
+/// Returns `bps` basis points of `amount`, rounding down
+pub fn bps_of(amount: u64, bps: u64) -> Option<u64> {
+    let share = (amount as u128)
+        .checked_mul(bps as u128)?
+        .checked_div(BPS_DENOMINATOR as u128)?;
+    if share > u64::MAX as u128 {
+        return None;
+    }
+    Some(share as u64)
+}
+
+/// Prize pool distribution computed on settlement
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrizeSplit {
+    /// Paid to sollotto rewards wallet
+    pub sollotto_reward: u64,
+    /// Prize of one winning line of every prize table tier
+    pub tier_payouts: [u64; MAX_PRIZE_TIERS],
+    /// Total prize of all winning lines, kept in prize vault for claims
+    pub reserved: u64,
+    /// Pools of tiers without winners, swept to holding wallet
+    pub sweep: u64,
+    /// Division remainders of tier pools, rolled to the next draw
+    pub dust: u64,
+}
+
+/// Splits `prize_pool` between sollotto reward and winners of every `prize_table` tier,
+/// sum of all parts of the split is always equal to `prize_pool`
+pub fn split_prize_pool(
+    prize_pool: u64,
+    prize_table: &PrizeTable,
+    tier_winners: &[u32; MAX_PRIZE_TIERS],
+) -> Option<PrizeSplit> {
+    let sollotto_reward = bps_of(prize_pool, SOLLOTTO_REWARD_BPS)?;
+    let mut remaining = prize_pool.checked_sub(sollotto_reward)?;
+
+    // Fixed prizes are paid first, then pool shares in table order,
+    // every tier is limited by the remaining pool
+    let mut tier_pools = [0u64; MAX_PRIZE_TIERS];
+    for kind in [PrizeKind::Fixed, PrizeKind::PoolShare].iter() {
+        for (i, tier) in prize_table.tiers().iter().enumerate() {
+            if tier.kind != *kind || tier_winners[i] == 0 {
+                continue;
+            }
+            let tier_pool = match tier.kind {
+                PrizeKind::Fixed => (tier_winners[i] as u64).checked_mul(tier.amount)?,
+                PrizeKind::PoolShare => bps_of(prize_pool, tier.amount)?,
+            };
+            tier_pools[i] = tier_pool.min(remaining);
+            remaining -= tier_pools[i];
+        }
+    }
+    let sweep = remaining;
+
+    let mut split = PrizeSplit {
+        sollotto_reward,
+        sweep,
+        ..Default::default()
+    };
+    for ((tier_payout, tier_pool), winners_count) in split
+        .tier_payouts
+        .iter_mut()
+        .zip(tier_pools.iter())
+        .zip(tier_winners.iter())
+    {
+        if *winners_count == 0 {
+            continue;
+        }
+        *tier_payout = tier_pool / *winners_count as u64;
+        let tier_reserved = *tier_payout * *winners_count as u64;
+        split.reserved = split.reserved.checked_add(tier_reserved)?;
+        split.dust = split.dust.checked_add(tier_pool - tier_reserved)?;
+    }
+
+    Some(split)
+}
+
+/// Counts distinct main balls of the ticket found among the winning main balls
+/// in any order, and checks that all bonus balls of the ticket are winning bonus balls.
+/// Digits are counted only at the same position and never match a bonus
+pub fn count_matches(
+    game_matrix: &GameMatrix,
+    ticket_number_arr: &[u8; 6],
+    winning_numbers: &[u8; 6],
+) -> (u8, bool) {
+    let main_count = game_matrix.main_count as usize;
+    if game_matrix.kind == GameKind::Digits {
+        let digit_matches = ticket_number_arr[..main_count]
+            .iter()
+            .zip(winning_numbers[..main_count].iter())
+            .filter(|(ticket_digit, winning_digit)| ticket_digit == winning_digit)
+            .count() as u8;
+        return (digit_matches, false);
+    }
+    let bonus_end = main_count + game_matrix.bonus_count as usize;
+    let (ticket_main, ticket_bonus) = ticket_number_arr[..bonus_end].split_at(main_count);
+    let (winning_main, winning_bonus) = winning_numbers[..bonus_end].split_at(main_count);
+
+    let main_matches = ticket_main
+        .iter()
+        .enumerate()
+        .filter(|(i, number)| {
+            !ticket_main[..*i].contains(number) && winning_main.contains(number)
+        })
+        .count() as u8;
+    let bonus_match = !ticket_bonus.is_empty()
+        && ticket_bonus
+            .iter()
+            .all(|number| winning_bonus.contains(number));
+
+    (main_matches, bonus_match)
+}
+
+/// Takes uniformly distributed number from 1 to `range` from random bytes,
+/// bytes above the largest multiple of `range` are rejected to avoid modulo bias
+fn sample_number(random_bytes: &mut impl Iterator<Item = u8>, range: u8) -> Option<u8> {
+    let limit = 256 - 256 % range as u16;
+    random_bytes
+        .find(|byte| (*byte as u16) < limit)
+        .map(|byte| byte % range + 1)
+}
+
+/// Draws number combination of the game matrix from `randomness`:
+/// distinct sorted main balls followed by distinct sorted bonus balls,
+/// or digits from 0 in drawn order that may repeat for `Digits` game.
+/// Randomness is expanded into `hash(randomness, block index)` blocks as needed,
+/// returns `None` if the game matrix is not valid
+pub fn draw_numbers(game_matrix: &GameMatrix, randomness: &[u8]) -> Option<[u8; 6]> {
+    if !game_matrix.is_valid() {
+        return None;
+    }
+    let mut random_bytes = (0u32..).flat_map(|block| {
+        hashv(&[randomness, &block.to_le_bytes()])
+            .to_bytes()
+            .to_vec()
+    });
+
+    let main_count = game_matrix.main_count as usize;
+    let mut numbers = [0u8; 6];
+    if game_matrix.kind == GameKind::Digits {
+        for digit in numbers[..main_count].iter_mut() {
+            *digit = sample_number(&mut random_bytes, game_matrix.main_range)? - 1;
+        }
+        return Some(numbers);
+    }
+
+    let bonus_end = main_count + game_matrix.bonus_count as usize;
+    for &(start, end, range) in [
+        (0, main_count, game_matrix.main_range),
+        (main_count, bonus_end, game_matrix.bonus_range),
+    ]
+    .iter()
+    {
+        let mut i = start;
+        while i < end {
+            let number = sample_number(&mut random_bytes, range)?;
+            if !numbers[start..i].contains(&number) {
+                numbers[i] = number;
+                i += 1;
+            }
+        }
+        numbers[start..end].sort_unstable();
+    }
+    Some(numbers)
+}
+
+/// Slots after sales close the seed can be revealed in, while SlotHashes sysvar
+/// still holds the hash of the first slot after sales close
+pub const SEED_REVEAL_WINDOW_SLOTS: u64 = slot_hashes::MAX_ENTRIES as u64;
+
+/// Time after draw time a Switchboard lottery must be drawn in, otherwise anyone can cancel it
+pub const DRAW_TIMEOUT_SECS: i64 = 86_400;
+
+/// Slots after the randomness request the Vrf round must be fulfilled in,
+/// otherwise anyone can cancel the overdue Switchboard lottery
+pub const VRF_FULFILLMENT_TIMEOUT_SLOTS: u64 = 9_000;
+
+/// Version of `draw_numbers` algorithm recorded in the draw proof
+pub const DRAW_ALGORITHM_VERSION: u8 = 1;
+
+/// Recomputes winning numbers of the lottery result from its draw proof,
+/// commit-reveal randomness is recomputed from the seed matching the seed commitment.
+/// Returns true if they match the winning numbers of the result
+pub fn verify_draw(lottery_result: &LotteryResultData) -> bool {
+    let draw_proof = &lottery_result.draw_proof;
+    match draw_proof.algorithm_version {
+        DRAW_ALGORITHM_VERSION => {
+            verify_seed_reveal(lottery_result)
+                && draw_numbers(&lottery_result.game_matrix, draw_proof.randomness())
+                    == Some(lottery_result.winning_numbers)
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if commit-reveal draw randomness is the committed seed mixed with
+/// the slot hash of the draw proof, always true for other randomness modes
+fn verify_seed_reveal(lottery_result: &LotteryResultData) -> bool {
+    let draw_proof = &lottery_result.draw_proof;
+    if draw_proof.randomness_mode != RandomnessMode::CommitReveal {
+        return true;
+    }
+    hash(&draw_proof.seed).to_bytes() == lottery_result.seed_commitment
+        && hashv(&[&draw_proof.seed, &draw_proof.slot_hash]).to_bytes()[..]
+            == *draw_proof.randomness()
+}
+
+/// Derives quick pick numbers of the ticket from the lottery draw seed
+pub fn quick_pick_numbers(
+    game_matrix: &GameMatrix,
+    draw_seed: &[u8; 32],
+    ticket_key: &Pubkey,
+) -> Option<[u8; 6]> {
+    let random = hashv(&[draw_seed, ticket_key.as_ref()]).to_bytes();
+    draw_numbers(game_matrix, &random)
+}
+
+/// Returns the ticket ledger hash after `buyer` purchase of `lines` in the ticket account
+/// at `slot`. Quick pick lines are hashed as purchased, before numbers assignment.
+/// Folding it over tickets in registration order replays the lottery ticket ledger hash
+pub fn next_ticket_ledger_hash(
+    ticket_ledger_hash: &[u8; 32],
+    ticket_key: &Pubkey,
+    buyer: &Pubkey,
+    lines: &[[u8; 6]],
+    slot: u64,
+) -> [u8; 32] {
+    let slot = slot.to_le_bytes();
+    let mut data = vec![&ticket_ledger_hash[..], ticket_key.as_ref(), buyer.as_ref()];
+    data.extend(lines.iter().map(|line| &line[..]));
+    data.push(&slot);
+    hashv(&data).to_bytes()
+}
+
 /// Program state handler.
 pub struct Processor;
 impl<'a> Processor {
@@ -46,35 +425,27 @@ impl<'a> Processor {
 
         let instruction = LotteryInstruction::unpack(instruction_data)?;
         match instruction {
-            LotteryInstruction::InitLottery {
-                lottery_id,
-                
-                holding_wallet,
-                rewards_wallet,
-               
-                randomness_account,
-            } => {
+            LotteryInstruction::InitLottery { lottery_id, config } => {
                 msg!("Instruction: InitLottery");
-                Self::process_init_lottery(
-                    program_id,
-                    accounts,
-                    lottery_id,
-                    holding_wallet,
-                    rewards_wallet,
-                    randomness_account,
-                )
+                Self::process_init_lottery(program_id, accounts, lottery_id, &config)
             }
 
             LotteryInstruction::PurchaseTicket {
+                charity,
                 user_wallet_pk,
                 ticket_number_arr,
+                max_price,
+                quick_pick,
             } => {
                 msg!("Instruction: PurchaseTicket");
                 Self::process_ticket_purchase(
                     program_id,
                     accounts,
+                    charity,
                     user_wallet_pk,
                     ticket_number_arr,
+                    max_price,
+                    quick_pick,
                 )
             }
 
@@ -88,704 +459,5337 @@ impl<'a> Processor {
                 Self::process_reward_winners(program_id, accounts)
             }
 
-        }
-    }
+            LotteryInstruction::UpdateSollottoWallets {
+                holding_wallet,
+                rewards_wallet,
+            } => {
+                msg!("Instruction: update sollotto wallets");
+                Self::process_update_sollotto_wallets(
+                    program_id,
+                    accounts,
+                    holding_wallet,
+                    rewards_wallet,
+                )
+            }
 
-    pub fn process_init_lottery(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        lottery_id: u32,
-        holding_wallet: Pubkey,
-        rewards_wallet: Pubkey,
-        randomness_account: Pubkey,
-    ) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
+            LotteryInstruction::UpdateTicketPrice {
+                ticket_price_lamports,
+                ticket_price_usd_cents,
+            } => {
+                msg!("Instruction: update ticket price");
+                Self::process_update_ticket_price(
+                    program_id,
+                    accounts,
+                    ticket_price_lamports,
+                    ticket_price_usd_cents,
+                )
+            }
 
-        // lottery data account
-        let lottery_data_account = next_account_info(accounts_iter)?;
+            LotteryInstruction::PurchaseTickets {
+                charity,
+                user_wallet_pk,
+                ticket_number_arrs,
+                max_price,
+            } => {
+                msg!("Instruction: PurchaseTickets");
+                Self::process_tickets_purchase(
+                    program_id,
+                    accounts,
+                    charity,
+                    user_wallet_pk,
+                    ticket_number_arrs,
+                    max_price,
+                )
+            }
 
-        // Check if program owns data account
-        if lottery_data_account.owner != program_id {
-            msg!("Lottery Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
-        }
+            LotteryInstruction::AssignQuickPicks {} => {
+                msg!("Instruction: assign quick picks");
+                Self::process_assign_quick_picks(program_id, accounts)
+            }
 
-        if !lottery_data_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+            LotteryInstruction::ClaimPrize {} => {
+                msg!("Instruction: claim prize");
+                Self::process_claim_prize(program_id, accounts)
+            }
 
-        let rent = &Rent::from_account_info(next_account_info(accounts_iter)?)?;
-        if !rent.is_exempt(
-            lottery_data_account.lamports(),
-            lottery_data_account.data_len(),
-        ) {
-            return Err(LotteryError::NotRentExempt.into());
-        }
+            LotteryInstruction::SettleBatch {} => {
+                msg!("Instruction: settle batch");
+                Self::process_settle_batch(program_id, accounts)
+            }
 
-        // Add data to account
-        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
-        if lottery_data.is_initialized {
-            msg!("Lottery data account already initialized");
-            return Err(LotteryError::Initialized.into());
-        }
+            LotteryInstruction::FinalizeSettlement {} => {
+                msg!("Instruction: finalize settlement");
+                Self::process_finalize_settlement(program_id, accounts)
+            }
 
-        lottery_data.is_initialized = true;
-        lottery_data.lottery_id = lottery_id;
-        lottery_data.holding_wallet = holding_wallet;
-        lottery_data.rewards_wallet = rewards_wallet;
-        lottery_data.randomness_account = randomness_account;
-        lottery_data.total_registrations = 0;
-        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+            LotteryInstruction::RolloverPrizePool {} => {
+                msg!("Instruction: rollover prize pool");
+                Self::process_rollover_prize_pool(program_id, accounts)
+            }
 
-        msg!("Data stored");
+            LotteryInstruction::CloseSales {} => {
+                msg!("Instruction: close sales");
+                Self::process_close_sales(program_id, accounts)
+            }
 
-        Ok(())
+            LotteryInstruction::RequestRandomness {
+                permission_bump_seed,
+                state_bump_seed,
+            } => {
+                msg!("Instruction: request randomness");
+                Self::process_request_randomness(
+                    program_id,
+                    accounts,
+                    permission_bump_seed,
+                    state_bump_seed,
+                )
+            }
+
+            LotteryInstruction::RevealSeed { seed } => {
+                msg!("Instruction: reveal seed");
+                Self::process_reveal_seed(program_id, accounts, &seed)
+            }
+
+            LotteryInstruction::CancelLottery {} => {
+                msg!("Instruction: cancel lottery");
+                Self::process_cancel_lottery(program_id, accounts)
+            }
+
+            LotteryInstruction::RefundTicket {} => {
+                msg!("Instruction: refund ticket");
+                Self::process_refund_ticket(program_id, accounts)
+            }
+        }
     }
 
-    pub fn process_ticket_purchase(
+    /// Checks that lottery data account is the program address derived from its data
+    fn check_lottery_account(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        charity: Pubkey,
-        user_wallet_pk: Pubkey,
-        ticket_number_arr: [u8; 6],
+        lottery_data_account: &AccountInfo,
+        lottery_data: &LotteryData,
     ) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
-        let lottery_data_account = next_account_info(accounts_iter)?;
-        let ticket_data_account = next_account_info(accounts_iter)?;
-        let user_funding_account = next_account_info(accounts_iter)?;
-        let holding_wallet_account = next_account_info(accounts_iter)?;
-        let user_lifetime_ticket_account = next_account_info(accounts_iter)?;
-        let lifetime_ticket_owner_account = next_account_info(accounts_iter)?;
-        let lifetime_ticket_mint_account = next_account_info(accounts_iter)?;
-        let rent = &Rent::from_account_info(next_account_info(accounts_iter)?)?;
-        let system_program_info = next_account_info(accounts_iter)?;
-        let spl_token_info = next_account_info(accounts_iter)?;
+        let lottery_key = Pubkey::create_program_address(
+            &[
+                LOTTERY_SEED,
+                lottery_data.authority.as_ref(),
+                &lottery_data.lottery_id.to_le_bytes(),
+                &[lottery_data.bump_seed],
+            ],
+            program_id,
+        )
+        .map_err(|_| LotteryError::InvalidLotteryAccount)?;
 
-        if lottery_data_account.owner != program_id {
-            msg!("Lottery Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        if ticket_data_account.owner != program_id {
-            msg!("Ticket Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
+        if lottery_key != *lottery_data_account.key {
+            msg!("Lottery data account is not the lottery program address");
+            return Err(LotteryError::InvalidLotteryAccount.into());
         }
+        Ok(())
+    }
 
-        if !lottery_data_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        if !user_funding_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        if !lifetime_ticket_owner_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+    /// Draws winning numbers of the lottery from the `provider` randomness
+    fn draw_winning_numbers(
+        lottery_data: &mut LotteryData,
+        provider: &dyn RandomnessProvider,
+    ) -> ProgramResult {
+        let randomness = provider.randomness(lottery_data)?;
+        let game_matrix = lottery_data.game_matrix;
+        let winning_numbers_arr = draw_numbers(&game_matrix, &randomness.value)
+            .ok_or(LotteryError::InvalidRandomResult)?;
+
+        if !game_matrix.is_valid_combination(&winning_numbers_arr) {
+            msg!("Winning numbers do not match game matrix");
+            return Err(LotteryError::InvalidNumber.into());
         }
 
-        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
-        //Check if lottery initisalised
-        if !lottery_data.is_initialized {
-            msg!("Ticket data account is not initialized");
-            return Err(LotteryError::NotInitialized.into());
+        lottery_data.winning_numbers = winning_numbers_arr;
+        lottery_data.draw_seed = hash(&randomness.value).to_bytes();
+        let mut draw_proof = DrawProof::new(
+            DRAW_ALGORITHM_VERSION,
+            lottery_data.randomness_mode,
+            randomness.account,
+            randomness.slot,
+            &randomness.value,
+        )
+        .ok_or(LotteryError::InvalidRandomResult)?;
+        draw_proof.seed = randomness.seed;
+        draw_proof.slot_hash = randomness.slot_hash;
+        lottery_data.draw_proof = draw_proof;
+        Ok(())
+    }
+
+    /// Checks that ticket sales window of the lottery is open at `clock` time
+    fn check_sales_window(lottery_data: &LotteryData, clock: &Clock) -> ProgramResult {
+        if clock.unix_timestamp < lottery_data.sales_open_ts {
+            msg!("Ticket sales open at {}", lottery_data.sales_open_ts);
+            return Err(LotteryError::SalesNotOpen.into());
         }
-        if lottery_data.is_finaled {
-            msg!("Lottery data account already finaled");
-            return Err(LotteryError::IsFinaled.into());
+        if clock.unix_timestamp >= lottery_data.sales_close_ts {
+            msg!("Ticket sales closed at {}", lottery_data.sales_close_ts);
+            return Err(LotteryError::SalesClosed.into());
         }
+        Ok(())
+    }
 
-        if *holding_wallet_account.key != lottery_data.holding_wallet {
-            msg!("Missing holding wallet");
-            return Err(LotteryError::InvalidSollottoAccount.into());
+    /// Checks that draw time of the lottery is reached at `clock` time
+    fn check_draw_time(lottery_data: &LotteryData, clock: &Clock) -> ProgramResult {
+        if clock.unix_timestamp < lottery_data.draw_after_ts {
+            msg!("Winning numbers can be drawn after {}", lottery_data.draw_after_ts);
+            return Err(LotteryError::DrawTooEarly.into());
         }
+        Ok(())
+    }
 
-        if user_funding_account.lamports() < sol_to_lamports(0.1) {
-            msg!("User cannot pay for ticket");
-            return Err(ProgramError::InsufficientFunds);
+    /// Checks that registrations and prize pool did not change since sales close
+    fn check_sales_snapshot(lottery_data: &LotteryData) -> ProgramResult {
+        if lottery_data.total_registrations != lottery_data.closed_registrations
+            || lottery_data.prize_pool_amount != lottery_data.closed_prize_pool
+        {
+            msg!(
+                "Sales closed with {} registrations and prize pool {}",
+                lottery_data.closed_registrations,
+                lottery_data.closed_prize_pool
+            );
+            return Err(LotteryError::SalesSnapshotMismatch.into());
         }
+        Ok(())
+    }
 
-        if !rent.is_exempt(
-            lottery_data_account.lamports(),
-            lottery_data_account.data_len(),
-        ) {
-            return Err(LotteryError::NotRentExempt.into());
+    /// Checks that lottery draw uses the `expected` randomness mode
+    fn check_randomness_mode(
+        lottery_data: &LotteryData,
+        expected: RandomnessMode,
+    ) -> ProgramResult {
+        if lottery_data.randomness_mode != expected {
+            msg!(
+                "Lottery randomness mode is {:?}, expected {:?}",
+                lottery_data.randomness_mode,
+                expected
+            );
+            return Err(LotteryError::InvalidRandomnessMode.into());
         }
+        Ok(())
+    }
 
-        if !rent.is_exempt(
-            ticket_data_account.lamports(),
-            ticket_data_account.data_len(),
-        ) {
-            return Err(LotteryError::NotRentExempt.into());
+    /// Checks that lottery is in the `expected` status
+    fn check_status(lottery_data: &LotteryData, expected: LotteryStatus) -> ProgramResult {
+        if lottery_data.status != expected {
+            msg!(
+                "Lottery status is {:?}, expected {:?}",
+                lottery_data.status,
+                expected
+            );
+            return Err(LotteryError::InvalidStatusTransition.into());
         }
+        Ok(())
+    }
 
-        let mut ticket_data = TicketData::unpack_unchecked(&ticket_data_account.data.borrow())?;
-        if ticket_data.is_purchased {
-            msg!("Ticket data account already purchased");
-            return Err(LotteryError::AlreadyPurchased.into());
+    /// Moves lottery to the `next` status if the transition is allowed
+    fn transition_status(lottery_data: &mut LotteryData, next: LotteryStatus) -> ProgramResult {
+        if !lottery_data.status.can_transition_to(next) {
+            msg!(
+                "Invalid lottery status transition: {:?} -> {:?}",
+                lottery_data.status,
+                next
+            );
+            return Err(LotteryError::InvalidStatusTransition.into());
         }
+        lottery_data.status = next;
+        Ok(())
+    }
 
-        for i in 0..5 {
-            if ticket_number_arr[i] < 1 || ticket_number_arr[i] > 69 {
-                msg!("Invalid value for one of from 1 to 5 number");
-                return Err(LotteryError::InvalidNumber.into());
-            }
+    /// Reads SOL/USD price from the lottery Chainlink feed, converts USD ticket price to lamports
+    fn get_usd_ticket_price<'b>(
+        lottery_data: &LotteryData,
+        clock: &Clock,
+        price_feed_account: &AccountInfo<'b>,
+        chainlink_program_info: &AccountInfo<'b>,
+    ) -> Result<u64, ProgramError> {
+        if *price_feed_account.key != lottery_data.price_feed {
+            msg!("Invalid price feed account");
+            return Err(LotteryError::InvalidPriceFeed.into());
         }
-        if ticket_number_arr[5] < 1 || ticket_number_arr[5] > 29 {
-            msg!("Invalid value for 6 number");
-            return Err(LotteryError::InvalidNumber.into());
+        if *chainlink_program_info.key != chainlink_store::id() {
+            msg!("Invalid Chainlink store program");
+            return Err(LotteryError::InvalidPriceFeed.into());
         }
 
-        ticket_data.is_purchased = true;
-        ticket_data.user_wallet_pk = user_wallet_pk;
-        ticket_data.ticket_number_arr = ticket_number_arr;
+        let round = chainlink::latest_round_data(
+            chainlink_program_info.clone(),
+            price_feed_account.clone(),
+        )?;
+        let decimals =
+            chainlink::decimals(chainlink_program_info.clone(), price_feed_account.clone())?;
 
-        lottery_data.total_registrations += 1;
-        
-      
+        if clock.unix_timestamp - round.timestamp as i64 > MAX_PRICE_FEED_AGE_SECS {
+            msg!("Price feed round {} is stale", round.round_id);
+            return Err(LotteryError::StalePriceFeed.into());
+        }
+        if round.answer <= 0 {
+            msg!("Price feed answer is not positive");
+            return Err(LotteryError::InvalidPriceFeed.into());
+        }
 
-        // Transfer 0.1 SOL into holding wallet from user_wallet
-        //We have to transfer SOL/USD acc to the live price feeds
-        let ticket_price = sol_to_lamports(0.1);
-        invoke(
-            &system_instruction::transfer(
-                &user_wallet_pk,
-                &lottery_data.holding_wallet,
-                ticket_price,
-            ),
-            &[
-                user_funding_account.clone(),
-                holding_wallet_account.clone(),
-                system_program_info.clone(),
-            ],
-        )?;
+        let ticket_price = usd_cents_to_lamports(
+            lottery_data.ticket_price_usd_cents,
+            round.answer as u128,
+            decimals,
+        )
+        .ok_or(LotteryError::Overflow)?;
+        msg!("Ticket price in lamports: {}", ticket_price);
+        Ok(ticket_price)
+    }
 
-        // Mint 1.0 Lifetime Ticket Token to user
-        //Need to mint the NFT here
-        let decimals = Mint::unpack(&lifetime_ticket_mint_account.data.borrow())?.decimals;
-        let amount = ui_amount_to_amount(1.0, decimals);
-        invoke(
-            &spl_token::instruction::mint_to(
-                &spl_token::id(),
-                lifetime_ticket_mint_account.key,
-                user_lifetime_ticket_account.key,
-                lifetime_ticket_owner_account.key,
-                &[],
-                amount,
-            )
-            .unwrap(),
+    /// Checks that prize vault is the program address of the lottery data account
+    fn check_vault_account(
+        program_id: &Pubkey,
+        lottery_data_account: &AccountInfo,
+        vault_account: &AccountInfo,
+        lottery_data: &LotteryData,
+    ) -> ProgramResult {
+        let vault_key = Pubkey::create_program_address(
             &[
-                spl_token_info.clone(),
-                lifetime_ticket_mint_account.clone(),
-                user_lifetime_ticket_account.clone(),
-                lifetime_ticket_owner_account.clone(),
+                VAULT_SEED,
+                lottery_data_account.key.as_ref(),
+                &[lottery_data.vault_bump_seed],
             ],
-        )?;
-
-        lottery_data.prize_pool_amount += ticket_price;
-
-        TicketData::pack(ticket_data, &mut ticket_data_account.data.borrow_mut())?;
-        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+            program_id,
+        )
+        .map_err(|_| LotteryError::InvalidVaultAccount)?;
 
+        if vault_key != *vault_account.key {
+            msg!("Prize vault is not the lottery vault program address");
+            return Err(LotteryError::InvalidVaultAccount.into());
+        }
         Ok(())
     }
 
-    pub fn process_store_winning_numbers(
+    /// Checks that token vault is the program address of the lottery data account
+    fn check_token_vault_account(
         program_id: &Pubkey,
-        accounts: &'a [AccountInfo<'a>],
+        lottery_data_account: &AccountInfo,
+        token_vault_account: &AccountInfo,
+        lottery_data: &LotteryData,
     ) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
-        let lottery_data_account = next_account_info(accounts_iter)?;
-
-        if lottery_data_account.owner != program_id {
-            msg!("Lottery Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
-        }
+        let token_vault_key = Pubkey::create_program_address(
+            &[
+                TOKEN_VAULT_SEED,
+                lottery_data_account.key.as_ref(),
+                &[lottery_data.token_vault_bump_seed],
+            ],
+            program_id,
+        )
+        .map_err(|_| LotteryError::InvalidVaultAccount)?;
 
-        if !lottery_data_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        if token_vault_key != *token_vault_account.key {
+            msg!("Token vault is not the lottery token vault program address");
+            return Err(LotteryError::InvalidVaultAccount.into());
         }
+        Ok(())
+    }
 
-        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
-        if !lottery_data.is_initialized {
-            msg!("Lottery Data account is not initialized");
-            return Err(LotteryError::NotInitialized.into());
-        }
-        if lottery_data.is_finaled {
-            msg!("Lottery Data account already finaled");
-            return Err(LotteryError::IsFinaled.into());
+    /// Checks that token account holds lottery mint and is owned by `owner`
+    fn check_token_account(
+        token_account: &AccountInfo,
+        owner: &Pubkey,
+        lottery_data: &LotteryData,
+    ) -> ProgramResult {
+        if *token_account.owner != spl_token::id() {
+            msg!("Token account is not owned by SPL Token program");
+            return Err(LotteryError::InvalidTokenAccount.into());
         }
-
-        // if *vrf_account_info.key != lottery_data.randomness_account {
-        //     return Err(LotteryError::InvalidSollottoAccount.into());
-        // }
-
-        let vrf_account_info = next_account_info(accounts_iter)?;
-        let vrf_account = VrfAccount::new(vrf_account_info)?;
-        let random_numbers = vrf_account.get_verified_randomness()?;
-        // drop(vrf_account);
-        if random_numbers.len() < 6 {
-            return Err(LotteryError::InvalidRandomResult.into());
+        let account = TokenAccount::unpack(&token_account.data.borrow())?;
+        if account.mint != lottery_data.token_mint || account.owner != *owner {
+            msg!("Token account {} is invalid", token_account.key);
+            return Err(LotteryError::InvalidTokenAccount.into());
         }
+        Ok(())
+    }
 
-        let mut winning_numbers_arr: [u8; 6] = [0; 6];
-        for i in 0..4 {
-            winning_numbers_arr[i] = random_numbers[i] % 49 + 1;
+    /// Transfers `amount` from the lottery prize vault, or its token vault for SPL token lottery.
+    /// `program_info` is the System program or SPL Token program account accordingly.
+    fn transfer_from_vault<'b>(
+        lottery_data_account: &AccountInfo<'b>,
+        lottery_data: &LotteryData,
+        vault_account: &AccountInfo<'b>,
+        token_vault_account: Option<&AccountInfo<'b>>,
+        program_info: &AccountInfo<'b>,
+        destination_account: &AccountInfo<'b>,
+        amount: u64,
+    ) -> ProgramResult {
+        let vault_signer_seeds: &[&[u8]] = &[
+            VAULT_SEED,
+            lottery_data_account.key.as_ref(),
+            &[lottery_data.vault_bump_seed],
+        ];
+
+        match token_vault_account {
+            Some(token_vault_account) => invoke_signed(
+                &spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    token_vault_account.key,
+                    destination_account.key,
+                    vault_account.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    token_vault_account.clone(),
+                    destination_account.clone(),
+                    vault_account.clone(),
+                    program_info.clone(),
+                ],
+                &[vault_signer_seeds],
+            ),
+            None => invoke_signed(
+                &system_instruction::transfer(vault_account.key, destination_account.key, amount),
+                &[
+                    vault_account.clone(),
+                    destination_account.clone(),
+                    program_info.clone(),
+                ],
+                &[vault_signer_seeds],
+            ),
         }
-        winning_numbers_arr[5] = random_numbers[5] % 26 + 1;
+    }
 
-        for i in 0..5 {
-            if winning_numbers_arr[i] < 1 || winning_numbers_arr[i] > 69 {
-                msg!("Invalid value for one of from 1 to 5 number");
-                return Err(LotteryError::InvalidNumber.into());
+    /// Marks `TicketData` or `TicketBookData` account as claimed
+    fn mark_ticket_claimed(ticket_account: &AccountInfo) -> ProgramResult {
+        let mut data = ticket_account.data.borrow_mut();
+        if data.len() == TicketBookData::LEN {
+            let mut ticket_book = TicketBookData::unpack_unchecked(&data)?;
+            if ticket_book.is_claimed {
+                return Err(LotteryError::AlreadyClaimed.into());
             }
+            ticket_book.is_claimed = true;
+            TicketBookData::pack(ticket_book, &mut data)
+        } else {
+            let mut ticket = TicketData::unpack_unchecked(&data)?;
+            if ticket.is_claimed {
+                return Err(LotteryError::AlreadyClaimed.into());
+            }
+            ticket.is_claimed = true;
+            TicketData::pack(ticket, &mut data)
         }
-        if winning_numbers_arr[5] < 1 || winning_numbers_arr[5] > 29 {
-            msg!("Invalid value for 6 number");
-            return Err(LotteryError::InvalidNumber.into());
-        }
-
-        lottery_data.is_finaled = true;
-        lottery_data.winning_numbers = winning_numbers_arr;
-
-        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
-
-        Ok(())
     }
 
-    pub fn process_reward_winners(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
-        let lottery_data_account = next_account_info(accounts_iter)?;
-        let lottery_result_account = next_account_info(accounts_iter)?;
-        let holding_wallet_account = next_account_info(accounts_iter)?;
-        let rewards_wallet_account = next_account_info(accounts_iter)?;
-        let system_program_info = next_account_info(accounts_iter)?;
-        let participants_accounts = accounts_iter.as_slice();
+    /// Unpacks user wallet, registration index and purchased lines
+    /// of `TicketData` or `TicketBookData` account purchased in the lottery
+    fn unpack_ticket_lines(
+        ticket_account: &AccountInfo,
+        lottery_key: &Pubkey,
+        lottery_data: &LotteryData,
+    ) -> Result<(Pubkey, u32, Vec<[u8; 6]>), ProgramError> {
+        let data = ticket_account.data.borrow();
+        let (
+            is_purchased,
+            ticket_lottery,
+            ticket_lottery_id,
+            quick_pick_pending,
+            user_wallet_pk,
+            ticket_index,
+            lines,
+        ) = if data.len() == TicketBookData::LEN {
+            let ticket_book = TicketBookData::unpack_unchecked(&data)?;
+            (
+                ticket_book.is_purchased,
+                ticket_book.lottery,
+                ticket_book.lottery_id,
+                false,
+                ticket_book.user_wallet_pk,
+                ticket_book.ticket_index,
+                ticket_book.purchased_lines().to_vec(),
+            )
+        } else {
+            let ticket = TicketData::unpack_unchecked(&data)?;
+            (
+                ticket.is_purchased,
+                ticket.lottery,
+                ticket.lottery_id,
+                ticket.is_quick_pick_pending(),
+                ticket.user_wallet_pk,
+                ticket.ticket_index,
+                vec![ticket.ticket_number_arr],
+            )
+        };
 
-        if lottery_data_account.owner != program_id {
-            msg!("Lottery Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
+        if !is_purchased {
+            msg!("Ticket data account is not purchased");
+            return Err(LotteryError::NotInitialized.into());
         }
-        if lottery_result_account.owner != program_id {
-            msg!("Lottery Result Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
+        Self::check_ticket_lottery(
+            ticket_account,
+            &ticket_lottery,
+            ticket_lottery_id,
+            lottery_key,
+            lottery_data,
+        )?;
+        if quick_pick_pending {
+            msg!("Quick pick ticket {} numbers are not assigned", ticket_account.key);
+            return Err(LotteryError::QuickPickNotAssigned.into());
         }
+        Ok((user_wallet_pk, ticket_index, lines))
+    }
 
-        if !lottery_data_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        if !holding_wallet_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+    /// Unpacks user wallet and paid price
+    /// of `TicketData` or `TicketBookData` account purchased in the lottery
+    fn unpack_ticket_refund(
+        ticket_account: &AccountInfo,
+        lottery_key: &Pubkey,
+        lottery_data: &LotteryData,
+    ) -> Result<(Pubkey, u64), ProgramError> {
+        let data = ticket_account.data.borrow();
+        let (is_purchased, ticket_lottery, ticket_lottery_id, user_wallet_pk, price_paid) =
+            if data.len() == TicketBookData::LEN {
+                let ticket_book = TicketBookData::unpack_unchecked(&data)?;
+                (
+                    ticket_book.is_purchased,
+                    ticket_book.lottery,
+                    ticket_book.lottery_id,
+                    ticket_book.user_wallet_pk,
+                    ticket_book.price_paid,
+                )
+            } else {
+                let ticket = TicketData::unpack_unchecked(&data)?;
+                (
+                    ticket.is_purchased,
+                    ticket.lottery,
+                    ticket.lottery_id,
+                    ticket.user_wallet_pk,
+                    ticket.price_paid,
+                )
+            };
 
-        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
-        if !lottery_data.is_initialized {
-            msg!("Lottery Data account is not initialized");
+        if !is_purchased {
+            msg!("Ticket data account is not purchased");
             return Err(LotteryError::NotInitialized.into());
         }
-        if !lottery_data.is_finaled {
-            msg!("Lottery Data account have not winning numbers");
-            return Err(LotteryError::NotFinaled.into());
-        }
-
-        // Check all sollotto keys
-        if *holding_wallet_account.key != lottery_data.holding_wallet {
-            return Err(LotteryError::InvalidSollottoAccount.into());
-        }
-        if *rewards_wallet_account.key != lottery_data.rewards_wallet {
-            return Err(LotteryError::InvalidSollottoAccount.into());
-        }
-        
+        Self::check_ticket_lottery(
+            ticket_account,
+            &ticket_lottery,
+            ticket_lottery_id,
+            lottery_key,
+            lottery_data,
+        )?;
+        Ok((user_wallet_pk, price_paid))
+    }
 
-        if (participants_accounts.len() / 2) as u32 != lottery_data.total_registrations {
+    /// Checks that the ticket is purchased in the current round of the lottery
+    fn check_ticket_lottery(
+        ticket_account: &AccountInfo,
+        ticket_lottery: &Pubkey,
+        ticket_lottery_id: u32,
+        lottery_key: &Pubkey,
+        lottery_data: &LotteryData,
+    ) -> ProgramResult {
+        if ticket_lottery != lottery_key || ticket_lottery_id != lottery_data.lottery_id {
             msg!(
-                "Invalid participants accounts size: {}",
-                participants_accounts.len()
+                "Ticket {} is purchased in lottery {} round {}",
+                ticket_account.key,
+                ticket_lottery,
+                ticket_lottery_id
             );
-            return Err(LotteryError::InvalidParticipantsAccounts.into());
+            return Err(LotteryError::InvalidTicketLottery.into());
         }
-        msg!(
-            "Participants accounts size: {}",
-            participants_accounts.len()
-        );
-
-        for i in (0..participants_accounts.len()).step_by(2) {
-            if participants_accounts[i].owner != program_id {
-                msg!("Ticket Data account does not have the correct program id");
-                return Err(ProgramError::IncorrectProgramId);
-            }
-            let data = TicketData::unpack_unchecked(&participants_accounts[i].data.borrow())?;
-            if !data.is_purchased {
-                msg!("Ticket data account is not purchased");
-                return Err(LotteryError::NotInitialized.into());
-            }
+        Ok(())
+    }
 
-            if data.user_wallet_pk != *participants_accounts[i + 1].key {
-                msg!("Bad user_wallet_pk in ticket data account");
-                return Err(LotteryError::InvalidParticipantsAccounts.into());
-            }
+    /// Checks that the lottery authority signed the instruction
+    fn check_lottery_authority(
+        lottery_authority_account: &AccountInfo,
+        lottery_data: &LotteryData,
+    ) -> ProgramResult {
+        if *lottery_authority_account.key != lottery_data.authority {
+            msg!("Invalid lottery authority");
+            return Err(LotteryError::InvalidAuthority.into());
         }
-
-        // Check winning numbers and find winner
-        let mut winners6 = Vec::new();
-        let mut winners5 = Vec::new();
-        let mut winners4 = Vec::new();
-        let mut winners3 = Vec::new();
-        for i in (0..participants_accounts.len()).step_by(2) {
-            let ticket = TicketData::unpack_unchecked(&participants_accounts[i].data.borrow())?;
-            let mut matched: i32 = 0;
-            for j in 0..5 {
-                if ticket.ticket_number_arr[j] == lottery_data.winning_numbers[j] {
-                    matched = matched + 1;
-                }
-            }
-            if matched == 6 {
-                msg!("Found winner {}", participants_accounts[i + 1].key);
-                winners6.push(&participants_accounts[i + 1]);
-            }
-            if matched == 5 {
-                msg!("Found tier 5 {}", participants_accounts[i + 1].key);
-                winners5.push(&participants_accounts[i + 1]);
-            }
-            if matched == 4 {
-                msg!("Found tier 4 {}", participants_accounts[i + 1].key);
-                winners4.push(&participants_accounts[i + 1]);
-            }
-            if matched == 3 {
-                msg!("Found tier 3 {}", participants_accounts[i + 1].key);
-                winners3.push(&participants_accounts[i + 1]);
-            }
+        if !lottery_authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
         }
+        Ok(())
+    }
 
-        if holding_wallet_account.lamports() < lottery_data.prize_pool_amount {
-            msg!("Model 1 holding wallet InsufficientFunds error");
-            return Err(ProgramError::InsufficientFunds);
+    /// Creates program address account of `space` bytes owned by `owner`, `payer` funds
+    /// its rent exempt balance. Lamports sent to the address beforehand do not block it
+    fn create_pda_account<'b>(
+        payer: &AccountInfo<'b>,
+        new_account: &AccountInfo<'b>,
+        system_program_info: &AccountInfo<'b>,
+        rent: &Rent,
+        space: usize,
+        owner: &Pubkey,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let required_lamports = rent
+            .minimum_balance(space)
+            .saturating_sub(new_account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, new_account.key, required_lamports),
+                &[
+                    payer.clone(),
+                    new_account.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
         }
+        invoke_signed(
+            &system_instruction::allocate(new_account.key, space as u64),
+            &[new_account.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(new_account.key, owner),
+            &[new_account.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )
+    }
 
-        let prize_pool = lamports_to_sol(lottery_data.prize_pool_amount);
-        msg!("Prize pool in SOL: {}", prize_pool);
+    pub fn process_init_lottery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        lottery_id: u32,
+        config: &LotteryConfig,
+    ) -> ProgramResult {
+        let LotteryConfig {
+            holding_wallet,
+            rewards_wallet,
+            randomness_account,
+            randomness_mode,
+            seed_commitment,
+            sales_open_ts,
+            sales_close_ts,
+            draw_after_ts,
+            game_matrix,
+            prize_table,
+            ticket_price_lamports,
+            ticket_price_usd_cents,
+            price_feed,
+            token_mint,
+        } = *config;
+        let accounts_iter = &mut accounts.iter();
 
-        
+        // lottery data account
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let rent_info = next_account_info(accounts_iter)?;
+        let rent = &Rent::from_account_info(rent_info)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
 
-        // 7. 5% of the prize pool is transferred to the "Avalor" wallet address
-        let solloto_reward = sol_to_lamports(prize_pool * 0.05);
-        msg!("Solloto reward in lamports: {}", solloto_reward);
-        // Transfer from lottery_data.holding_wallet to solloto_rewards_wallet
-        invoke(
-            &system_instruction::transfer(
-                &lottery_data.holding_wallet,
-                &lottery_data.rewards_wallet,
-                solloto_reward,
-            ),
-            &[
-                holding_wallet_account.clone(),
-                rewards_wallet_account.clone(),
-                system_program_info.clone(),
-            ],
-        )?;
+        if !lottery_authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
 
-        lottery_data.prize_pool_amount -= solloto_reward;
+        if !game_matrix.is_valid() {
+            msg!("Invalid game matrix");
+            return Err(LotteryError::InvalidGameMatrix.into());
+        }
 
-        
+        if !prize_table.is_valid(&game_matrix) {
+            msg!("Invalid prize table");
+            return Err(LotteryError::InvalidPrizeTable.into());
+        }
+        match prize_table.pool_share_bps() {
+            Some(pool_share_bps)
+                if pool_share_bps
+                    .checked_add(SOLLOTTO_REWARD_BPS)
+                    .map_or(false, |bps| bps <= BPS_DENOMINATOR) => {}
+            _ => {
+                msg!("Prize table pool shares exceed prize pool");
+                return Err(LotteryError::InvalidPrizeTable.into());
+            }
+        }
 
-        // Process rewards
-        let mut all_winners = Vec::new();
-        let mut winner_rewards = Vec::new();
-        let mut winners6_pool = prize_pool * 0.95;
-
-        // 3 tiers
-        let winner3_reward = sol_to_lamports(0.1);
-        msg!("Winners(3 tier) number {}", winners3.len());
-        msg!("Winner(3 tier) reward in lamports: {}", winner3_reward);
-        for winner3 in winners3 {
-            all_winners.push(winner3);
-            winner_rewards.push(winner3_reward);
-            winners6_pool = winners6_pool - 0.1;
-        }
-
-        // 4 tiers
-        let winners4_pool = prize_pool * 0.05;
-        let winner4_reward ;
-        if winners4.len() != 0 {
-            winner4_reward = sol_to_lamports(winners4_pool / winners4.len() as f64);
-            winners6_pool = winners6_pool - winners4_pool;
-        } else {
-            winner4_reward = 0;
+        if randomness_mode == RandomnessMode::CommitReveal && seed_commitment == [0; 32] {
+            msg!("Seed commitment is required for commit-reveal randomness");
+            return Err(LotteryError::InvalidRandomnessMode.into());
         }
-        msg!("Winners(4 tier) number {}", winners4.len());
-        msg!("Winner(4 tier) reward in lamports: {}", winner4_reward);
-        for winner4 in winners4 {
-            all_winners.push(winner4);
-            winner_rewards.push(winner4_reward);
+
+        if sales_open_ts >= sales_close_ts || sales_close_ts > draw_after_ts {
+            msg!("Sales must open before close and close not later than draw");
+            return Err(LotteryError::InvalidSalesSchedule.into());
         }
 
-        // 5 tiers
-        let winners5_pool = prize_pool * 0.05;
-        let winner5_reward ;
-        if winners5.len() != 0 {
-            winner5_reward = sol_to_lamports(winners5_pool / winners5.len() as f64);
-            winners6_pool = winners6_pool - winners5_pool;
-        } else {
-            winner5_reward = 0;
+        if ticket_price_lamports == 0 && ticket_price_usd_cents == 0 {
+            msg!("Ticket price must be greater than zero");
+            return Err(LotteryError::InvalidTicketPrice.into());
         }
-        msg!("Winners(5 tier) number {}", winners5.len());
-        msg!("Winner(5 tier) reward in lamports: {}", winner5_reward);
-        for winner5 in winners5 {
-            all_winners.push(winner5);
-            winner_rewards.push(winner5_reward);
+        if ticket_price_usd_cents != 0 && price_feed == Pubkey::default() {
+            msg!("USD ticket price requires a price feed");
+            return Err(LotteryError::InvalidPriceFeed.into());
         }
 
-        // 6 tiers - perfect match
-        let winner6_reward ;
-        if winners6.len() != 0 {
-            winner6_reward = sol_to_lamports(winners6_pool / winners6.len() as f64);
-        } else {
-            winner6_reward = 0;
+        let (lottery_key, bump_seed) =
+            find_lottery_address(program_id, lottery_authority_account.key, lottery_id);
+        if lottery_key != *lottery_data_account.key {
+            msg!("Lottery data account is not the lottery program address");
+            return Err(LotteryError::InvalidLotteryAccount.into());
         }
-        msg!("Winners number {}", winners6.len());
-        msg!("Winner reward in lamports: {}", winner6_reward);
-        for winner6 in winners6 {
-            all_winners.push(winner6);
-            winner_rewards.push(winner6_reward);
+        let (vault_key, vault_bump_seed) = find_vault_address(program_id, &lottery_key);
+        if vault_key != *vault_account.key {
+            msg!("Prize vault is not the lottery vault program address");
+            return Err(LotteryError::InvalidVaultAccount.into());
         }
 
-        for i in 0..all_winners.len() {
-            // Transfer from lottery_data.holding_wallet to winner_wallet
-            invoke(
-                &system_instruction::transfer(
-                    &lottery_data.holding_wallet,
-                    &all_winners[i].key,
-                    winner_rewards[i],
-                ),
+        // Create lottery data account, authority pays the rent
+        if lottery_data_account.data_is_empty() {
+            Self::create_pda_account(
+                lottery_authority_account,
+                lottery_data_account,
+                system_program_info,
+                rent,
+                LotteryData::LEN,
+                program_id,
                 &[
-                    holding_wallet_account.clone(),
-                    all_winners[i].clone(),
-                    system_program_info.clone(),
+                    LOTTERY_SEED,
+                    lottery_authority_account.key.as_ref(),
+                    &lottery_id.to_le_bytes(),
+                    &[bump_seed],
                 ],
             )?;
-
-            lottery_data.prize_pool_amount -= winner_rewards[i];
         }
 
-        // Create lottery result acc info
-        let lottery_result = LotteryResultData {
-            lottery_id: lottery_data.lottery_id,
-            winning_numbers: lottery_data.winning_numbers,
-        };
+        // Check if program owns data account
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
 
-        // Clear lottery acc for new lottery
-        lottery_data.is_initialized = false;
-        lottery_data.is_finaled = false;
-        lottery_data.winning_numbers = [0, 0];
-        lottery_data.total_registrations = 0;
-        lottery_data.lottery_id = 0;
+        if !rent.is_exempt(
+            lottery_data_account.lamports(),
+            lottery_data_account.data_len(),
+        ) {
+            return Err(LotteryError::NotRentExempt.into());
+        }
 
-        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
-        LotteryResultData::pack(
-            lottery_result,
-            &mut lottery_result_account.data.borrow_mut(),
-        )?;
+        // Add data to account
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if lottery_data.is_initialized {
+            msg!("Lottery data account already initialized");
+            return Err(LotteryError::Initialized.into());
+        }
 
-        Ok(())
-    }
+        // Create token vault owned by the prize vault, authority pays the rent
+        if token_mint != Pubkey::default() {
+            let token_mint_account = next_account_info(accounts_iter)?;
+            let token_vault_account = next_account_info(accounts_iter)?;
+            let spl_token_info = next_account_info(accounts_iter)?;
 
-    
+            if ticket_price_usd_cents != 0 {
+                msg!("USD ticket price is not supported for SPL token lottery");
+                return Err(LotteryError::InvalidTicketPrice.into());
+            }
+            if *token_mint_account.key != token_mint || *token_mint_account.owner != spl_token::id()
+            {
+                msg!("Invalid token mint account");
+                return Err(LotteryError::InvalidTokenAccount.into());
+            }
+            Mint::unpack(&token_mint_account.data.borrow())?;
 
-    pub fn process_update_sollotto_wallets(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        holding_wallet: Pubkey,
-        rewards_wallet: Pubkey,
-        slot_holders_rewards_wallet: Pubkey,
-        sollotto_labs_wallet: Pubkey,
-    ) -> ProgramResult {
-        let accounts_iter = &mut accounts.iter();
-        let lottery_data_account = next_account_info(accounts_iter)?;
+            let (token_vault_key, token_vault_bump_seed) =
+                find_token_vault_address(program_id, lottery_data_account.key);
+            if token_vault_key != *token_vault_account.key {
+                msg!("Token vault is not the lottery token vault program address");
+                return Err(LotteryError::InvalidVaultAccount.into());
+            }
 
-        if lottery_data_account.owner != program_id {
-            msg!("Lottery Data account does not have the correct program id");
-            return Err(ProgramError::IncorrectProgramId);
-        }
+            if token_vault_account.data_is_empty() {
+                Self::create_pda_account(
+                    lottery_authority_account,
+                    token_vault_account,
+                    system_program_info,
+                    rent,
+                    TokenAccount::LEN,
+                    &spl_token::id(),
+                    &[
+                        TOKEN_VAULT_SEED,
+                        lottery_data_account.key.as_ref(),
+                        &[token_vault_bump_seed],
+                    ],
+                )?;
+                invoke(
+                    &spl_token::instruction::initialize_account2(
+                        &spl_token::id(),
+                        token_vault_account.key,
+                        token_mint_account.key,
+                        &vault_key,
+                    )?,
+                    &[
+                        token_vault_account.clone(),
+                        token_mint_account.clone(),
+                        rent_info.clone(),
+                        spl_token_info.clone(),
+                    ],
+                )?;
+            }
 
-        if !lottery_data_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+            lottery_data.token_mint = token_mint;
+            lottery_data.token_vault_bump_seed = token_vault_bump_seed;
         }
 
-        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
-        if !lottery_data.is_initialized {
-            msg!("Lottery Data account is not initialized");
-            return Err(LotteryError::NotInitialized.into());
+        // Prize vault holds rent exempt balance besides the prize pool, authority pays it
+        if token_mint == Pubkey::default() {
+            let vault_rent_reserve = rent.minimum_balance(0);
+            let required_lamports = vault_rent_reserve.saturating_sub(vault_account.lamports());
+            if required_lamports > 0 {
+                invoke(
+                    &system_instruction::transfer(
+                        lottery_authority_account.key,
+                        vault_account.key,
+                        required_lamports,
+                    ),
+                    &[
+                        lottery_authority_account.clone(),
+                        vault_account.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            lottery_data.vault_rent_reserve = vault_rent_reserve;
         }
 
+        lottery_data.is_initialized = true;
+        lottery_data.lottery_id = lottery_id;
         lottery_data.holding_wallet = holding_wallet;
         lottery_data.rewards_wallet = rewards_wallet;
-        lottery_data.slot_holders_rewards_wallet = slot_holders_rewards_wallet;
-
+        lottery_data.randomness_account = randomness_account;
+        lottery_data.randomness_mode = randomness_mode;
+        lottery_data.seed_commitment = seed_commitment;
+        lottery_data.sales_open_ts = sales_open_ts;
+        lottery_data.sales_close_ts = sales_close_ts;
+        lottery_data.draw_after_ts = draw_after_ts;
+        lottery_data.game_matrix = game_matrix;
+        lottery_data.prize_table = prize_table;
+        lottery_data.ticket_price_lamports = ticket_price_lamports;
+        lottery_data.ticket_price_usd_cents = ticket_price_usd_cents;
+        lottery_data.price_feed = price_feed;
+        lottery_data.status = LotteryStatus::Open;
+        lottery_data.authority = *lottery_authority_account.key;
+        lottery_data.bump_seed = bump_seed;
+        lottery_data.vault_bump_seed = vault_bump_seed;
+        lottery_data.total_registrations = 0;
+        lottery_data.settle_cursor = 0;
         LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
 
+        msg!("Data stored");
+
         Ok(())
     }
-}
 
-// Unit tests
-#[cfg(test)]
-mod test {
-    use super::*;
-    use solana_program::{instruction::Instruction, program_pack::Pack};
-    use solana_sdk::account::{
-        create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
-        ReadableAccount,
-    };
-    use spl_token::state::Account;
+    pub fn process_ticket_purchase(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        charity: Pubkey,
+        user_wallet_pk: Pubkey,
+        ticket_number_arr: [u8; 6],
+        max_price: u64,
+        quick_pick: bool,
+    ) -> ProgramResult {
+        Self::purchase_lines(
+            program_id,
+            accounts,
+            charity,
+            user_wallet_pk,
+            &[ticket_number_arr],
+            max_price,
+            false,
+            quick_pick,
+        )
+    }
 
-    fn lottery_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(LotteryData::get_packed_len())
+    pub fn process_tickets_purchase(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        charity: Pubkey,
+        user_wallet_pk: Pubkey,
+        ticket_number_arrs: Vec<[u8; 6]>,
+        max_price: u64,
+    ) -> ProgramResult {
+        if ticket_number_arrs.is_empty() || ticket_number_arrs.len() > MAX_TICKET_BOOK_LINES {
+            msg!("Invalid ticket lines count: {}", ticket_number_arrs.len());
+            return Err(LotteryError::InvalidTicketLinesCount.into());
+        }
+        Self::purchase_lines(
+            program_id,
+            accounts,
+            charity,
+            user_wallet_pk,
+            &ticket_number_arrs,
+            max_price,
+            true,
+            false,
+        )
     }
 
-    fn ticket_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(TicketData::get_packed_len())
+    /// Purchases `ticket_number_arrs` lines into ticket data account,
+    /// which is a `TicketBookData` if `ticket_book` is set and `TicketData` otherwise.
+    /// Quick pick `TicketData` is purchased without numbers.
+    fn purchase_lines(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        charity: Pubkey,
+        user_wallet_pk: Pubkey,
+        ticket_number_arrs: &[[u8; 6]],
+        max_price: u64,
+        ticket_book: bool,
+        quick_pick: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let ticket_data_account = next_account_info(accounts_iter)?;
+        let user_funding_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let user_lifetime_ticket_account = next_account_info(accounts_iter)?;
+        let lifetime_ticket_owner_account = next_account_info(accounts_iter)?;
+        let lifetime_ticket_mint_account = next_account_info(accounts_iter)?;
+        let rent = &Rent::from_account_info(next_account_info(accounts_iter)?)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+        let spl_token_info = next_account_info(accounts_iter)?;
+        let clock = &Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if ticket_data_account.owner != program_id {
+            msg!("Ticket Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if !user_funding_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !lifetime_ticket_owner_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        //Check if lottery initisalised
+        if !lottery_data.is_initialized {
+            msg!("Ticket data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Open)?;
+        Self::check_sales_window(&lottery_data, clock)?;
+
+        Self::check_vault_account(program_id, lottery_data_account, vault_account, &lottery_data)?;
+
+        // USD priced lottery pays by the live SOL/USD Chainlink price,
+        // SPL token lottery price is in token base units
+        let ticket_price = if lottery_data.ticket_price_usd_cents != 0 {
+            let price_feed_account = next_account_info(accounts_iter)?;
+            let chainlink_program_info = next_account_info(accounts_iter)?;
+            Self::get_usd_ticket_price(
+                &lottery_data,
+                clock,
+                price_feed_account,
+                chainlink_program_info,
+            )?
+        } else {
+            lottery_data.ticket_price_lamports
+        };
+
+        let lines_count = ticket_number_arrs.len() as u64;
+        let ticket_price = ticket_price
+            .checked_mul(lines_count)
+            .ok_or(LotteryError::Overflow)?;
+        if ticket_price > max_price {
+            msg!("Ticket price {} exceeds max price {}", ticket_price, max_price);
+            return Err(LotteryError::PriceExceedsMax.into());
+        }
+
+        let token_accounts = if lottery_data.is_token_lottery() {
+            let user_token_account = next_account_info(accounts_iter)?;
+            let token_vault_account = next_account_info(accounts_iter)?;
+            Self::check_token_account(user_token_account, &user_wallet_pk, &lottery_data)?;
+            Self::check_token_vault_account(
+                program_id,
+                lottery_data_account,
+                token_vault_account,
+                &lottery_data,
+            )?;
+            Some((user_token_account, token_vault_account))
+        } else {
+            None
+        };
+
+        let user_balance = match token_accounts {
+            Some((user_token_account, _)) => {
+                TokenAccount::unpack(&user_token_account.data.borrow())?.amount
+            }
+            None => user_funding_account.lamports(),
+        };
+        if user_balance < ticket_price {
+            msg!("User cannot pay for ticket");
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        if !rent.is_exempt(
+            lottery_data_account.lamports(),
+            lottery_data_account.data_len(),
+        ) {
+            return Err(LotteryError::NotRentExempt.into());
+        }
+
+        if !rent.is_exempt(
+            ticket_data_account.lamports(),
+            ticket_data_account.data_len(),
+        ) {
+            return Err(LotteryError::NotRentExempt.into());
+        }
+
+        for ticket_number_arr in ticket_number_arrs {
+            if !quick_pick
+                && !lottery_data
+                    .game_matrix
+                    .is_valid_combination(ticket_number_arr)
+            {
+                msg!("Ticket numbers do not match game matrix");
+                return Err(LotteryError::InvalidNumber.into());
+            }
+        }
+
+        if ticket_book {
+            let mut ticket_book_data =
+                TicketBookData::unpack_unchecked(&ticket_data_account.data.borrow())?;
+            if ticket_book_data.is_purchased {
+                msg!("Ticket book data account already purchased");
+                return Err(LotteryError::AlreadyPurchased.into());
+            }
+
+            ticket_book_data.is_purchased = true;
+            ticket_book_data.charity = charity;
+            ticket_book_data.user_wallet_pk = user_wallet_pk;
+            ticket_book_data.lines_count = ticket_number_arrs.len() as u8;
+            ticket_book_data.ticket_index = lottery_data.total_registrations;
+            ticket_book_data.lottery = *lottery_data_account.key;
+            ticket_book_data.lottery_id = lottery_data.lottery_id;
+            ticket_book_data.purchase_slot = clock.slot;
+            ticket_book_data.price_paid = ticket_price;
+            ticket_book_data.lines[..ticket_number_arrs.len()].copy_from_slice(ticket_number_arrs);
+            lottery_data.ticket_ledger_hash = next_ticket_ledger_hash(
+                &lottery_data.ticket_ledger_hash,
+                ticket_data_account.key,
+                &user_wallet_pk,
+                ticket_number_arrs,
+                clock.slot,
+            );
+            TicketBookData::pack(ticket_book_data, &mut ticket_data_account.data.borrow_mut())?;
+        } else {
+            let mut ticket_data = TicketData::unpack_unchecked(&ticket_data_account.data.borrow())?;
+            if ticket_data.is_purchased {
+                msg!("Ticket data account already purchased");
+                return Err(LotteryError::AlreadyPurchased.into());
+            }
+
+            ticket_data.is_purchased = true;
+            ticket_data.charity = charity;
+            ticket_data.user_wallet_pk = user_wallet_pk;
+            ticket_data.quick_pick = quick_pick;
+            ticket_data.ticket_index = lottery_data.total_registrations;
+            ticket_data.lottery = *lottery_data_account.key;
+            ticket_data.lottery_id = lottery_data.lottery_id;
+            ticket_data.purchase_slot = clock.slot;
+            ticket_data.price_paid = ticket_price;
+            ticket_data.quick_pick_assigned = false;
+            ticket_data.ticket_number_arr = if quick_pick {
+                [0; 6]
+            } else {
+                ticket_number_arrs[0]
+            };
+            lottery_data.ticket_ledger_hash = next_ticket_ledger_hash(
+                &lottery_data.ticket_ledger_hash,
+                ticket_data_account.key,
+                &user_wallet_pk,
+                &[ticket_data.ticket_number_arr],
+                clock.slot,
+            );
+            TicketData::pack(ticket_data, &mut ticket_data_account.data.borrow_mut())?;
+        }
+
+        lottery_data.total_registrations = lottery_data
+            .total_registrations
+            .checked_add(lines_count as u32)
+            .ok_or(LotteryError::Overflow)?;
+
+        // Transfer ticket price into prize vault from user_wallet
+        match token_accounts {
+            Some((user_token_account, token_vault_account)) => invoke(
+                &spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    user_token_account.key,
+                    token_vault_account.key,
+                    &user_wallet_pk,
+                    &[],
+                    ticket_price,
+                )?,
+                &[
+                    user_token_account.clone(),
+                    token_vault_account.clone(),
+                    user_funding_account.clone(),
+                    spl_token_info.clone(),
+                ],
+            )?,
+            None => invoke(
+                &system_instruction::transfer(&user_wallet_pk, vault_account.key, ticket_price),
+                &[
+                    user_funding_account.clone(),
+                    vault_account.clone(),
+                    system_program_info.clone(),
+                ],
+            )?,
+        }
+
+        // Mint 1.0 Lifetime Ticket Token to user for every line
+        //Need to mint the NFT here
+        let decimals = Mint::unpack(&lifetime_ticket_mint_account.data.borrow())?.decimals;
+        let amount = ui_amount_to_amount(1.0, decimals)
+            .checked_mul(lines_count)
+            .ok_or(LotteryError::Overflow)?;
+        invoke(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                lifetime_ticket_mint_account.key,
+                user_lifetime_ticket_account.key,
+                lifetime_ticket_owner_account.key,
+                &[],
+                amount,
+            )
+            .unwrap(),
+            &[
+                spl_token_info.clone(),
+                lifetime_ticket_mint_account.clone(),
+                user_lifetime_ticket_account.clone(),
+                lifetime_ticket_owner_account.clone(),
+            ],
+        )?;
+
+        lottery_data.prize_pool_amount = lottery_data
+            .prize_pool_amount
+            .checked_add(ticket_price)
+            .ok_or(LotteryError::Overflow)?;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    fn lottery_result_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(LotteryResultData::get_packed_len())
+    pub fn process_store_winning_numbers(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+
+        let vrf_account_info = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+        match lottery_data.randomness_mode {
+            RandomnessMode::SwitchboardVrf => {
+                Self::check_status(&lottery_data, LotteryStatus::AwaitingRandomness)?;
+                Self::check_draw_time(&lottery_data, &clock)?;
+                Self::transition_status(&mut lottery_data, LotteryStatus::Drawn)?;
+                let provider = SwitchboardVrf { vrf_account_info };
+                Self::draw_winning_numbers(&mut lottery_data, &provider)?;
+            }
+            #[cfg(feature = "test-randomness")]
+            RandomnessMode::Deterministic => {
+                Self::check_status(&lottery_data, LotteryStatus::SalesClosed)?;
+                Self::check_draw_time(&lottery_data, &clock)?;
+                Self::transition_status(&mut lottery_data, LotteryStatus::Drawn)?;
+                Self::draw_winning_numbers(&mut lottery_data, &Deterministic)?;
+            }
+            RandomnessMode::CommitReveal => {
+                msg!("Commit-reveal lottery is drawn by RevealSeed");
+                return Err(LotteryError::InvalidRandomnessMode.into());
+            }
+        }
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    fn mint_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(spl_token::state::Mint::LEN)
+    pub fn process_close_sales(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        if clock.unix_timestamp < lottery_data.sales_close_ts {
+            msg!("Sales can be closed after {}", lottery_data.sales_close_ts);
+            return Err(LotteryError::SalesCloseTooEarly.into());
+        }
+        // Commit-reveal draw mixes the hash of the first slot after sales close, SlotHashes
+        // keeps it for `SEED_REVEAL_WINDOW_SLOTS` only, so the draw must be allowed by then
+        if lottery_data.randomness_mode == RandomnessMode::CommitReveal {
+            Self::check_draw_time(&lottery_data, &clock)?;
+        }
+        Self::transition_status(&mut lottery_data, LotteryStatus::SalesClosed)?;
+
+        if lottery_data.randomness_mode == RandomnessMode::SwitchboardVrf {
+            let vrf_account_info = next_account_info(accounts_iter)?;
+            if *vrf_account_info.key != lottery_data.randomness_account {
+                msg!("Vrf account is not the lottery randomness account");
+                return Err(LotteryError::InvalidRandomnessAccount.into());
+            }
+            // Draw must use a round fulfilled after sales close
+            lottery_data.close_vrf_counter = vrf_round_counter(vrf_account_info)?;
+        }
+        lottery_data.sales_close_slot = clock.slot;
+        // Settlement requires registrations and prize pool sold until sales close
+        lottery_data.closed_registrations = lottery_data.total_registrations;
+        lottery_data.closed_prize_pool = lottery_data.prize_pool_amount;
+        msg!(
+            "Sales closed at slot {} with {} registrations",
+            clock.slot,
+            lottery_data.total_registrations
+        );
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    fn account_minimum_balance() -> u64 {
-        Rent::default().minimum_balance(spl_token::state::Account::LEN)
+    pub fn process_request_randomness(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'a>],
+        permission_bump_seed: u8,
+        state_bump_seed: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let vrf_account_info = next_account_info(accounts_iter)?;
+        let switchboard_program_info = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        Self::check_randomness_mode(&lottery_data, RandomnessMode::SwitchboardVrf)?;
+        Self::transition_status(&mut lottery_data, LotteryStatus::AwaitingRandomness)?;
+
+        if *vrf_account_info.key != lottery_data.randomness_account {
+            msg!("Vrf account is not the lottery randomness account");
+            return Err(LotteryError::InvalidRandomnessAccount.into());
+        }
+
+        if *switchboard_program_info.key != switchboard::id() {
+            msg!("Switchboard program account is invalid");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // Draw must use a round fulfilled after the request
+        lottery_data.request_vrf_counter = vrf_round_counter(vrf_account_info)?;
+        lottery_data.randomness_request_slot = clock.slot;
+
+        let oracle_queue_info = next_account_info(accounts_iter)?;
+        let queue_authority_info = next_account_info(accounts_iter)?;
+        let data_buffer_info = next_account_info(accounts_iter)?;
+        let permission_info = next_account_info(accounts_iter)?;
+        let escrow_info = next_account_info(accounts_iter)?;
+        let payer_wallet_info = next_account_info(accounts_iter)?;
+        let recent_blockhashes_info = next_account_info(accounts_iter)?;
+        let program_state_info = next_account_info(accounts_iter)?;
+        let spl_token_info = next_account_info(accounts_iter)?;
+        let request_accounts = switchboard::VrfRequestAccounts {
+            oracle_queue: *oracle_queue_info.key,
+            queue_authority: *queue_authority_info.key,
+            data_buffer: *data_buffer_info.key,
+            permission: *permission_info.key,
+            escrow: *escrow_info.key,
+            payer_wallet: *payer_wallet_info.key,
+            program_state: *program_state_info.key,
+        };
+
+        // Lottery data account is the Vrf account authority, it signs the request,
+        // lottery authority pays the request fee
+        invoke_signed(
+            &switchboard::request_randomness(
+                vrf_account_info.key,
+                lottery_data_account.key,
+                lottery_authority_account.key,
+                &request_accounts,
+                permission_bump_seed,
+                state_bump_seed,
+            ),
+            &[
+                lottery_data_account.clone(),
+                vrf_account_info.clone(),
+                oracle_queue_info.clone(),
+                queue_authority_info.clone(),
+                data_buffer_info.clone(),
+                permission_info.clone(),
+                escrow_info.clone(),
+                payer_wallet_info.clone(),
+                lottery_authority_account.clone(),
+                recent_blockhashes_info.clone(),
+                program_state_info.clone(),
+                spl_token_info.clone(),
+                switchboard_program_info.clone(),
+            ],
+            &[&[
+                LOTTERY_SEED,
+                lottery_data.authority.as_ref(),
+                &lottery_data.lottery_id.to_le_bytes(),
+                &[lottery_data.bump_seed],
+            ]],
+        )?;
+        msg!("Randomness requested at slot {}", clock.slot);
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
     }
 
-    fn do_process(instruction: Instruction, accounts: Vec<&mut SolanaAccount>) -> ProgramResult {
-        let mut meta = instruction
-            .accounts
-            .iter()
-            .zip(accounts)
-            .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
-            .collect::<Vec<_>>();
+    pub fn process_reveal_seed(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seed: &[u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let slot_hashes_info = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
 
-        let account_infos = create_is_signer_account_infos(&mut meta);
-        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        Self::check_randomness_mode(&lottery_data, RandomnessMode::CommitReveal)?;
+        Self::check_status(&lottery_data, LotteryStatus::SalesClosed)?;
+        Self::check_draw_time(&lottery_data, &clock)?;
+        Self::transition_status(&mut lottery_data, LotteryStatus::Drawn)?;
+        Self::draw_winning_numbers(
+            &mut lottery_data,
+            &CommitReveal {
+                seed: *seed,
+                slot_hashes_info,
+            },
+        )?;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_cancel_lottery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+
+        if lottery_data.randomness_mode == RandomnessMode::SwitchboardVrf {
+            // Anyone can cancel once the draw is overdue and the Vrf request is not fulfilled
+            let draw_deadline_ts = lottery_data.draw_after_ts.saturating_add(DRAW_TIMEOUT_SECS);
+            if clock.unix_timestamp <= draw_deadline_ts {
+                msg!("Lottery can be cancelled after {}", draw_deadline_ts);
+                return Err(LotteryError::CancelTooEarly.into());
+            }
+            if lottery_data.status == LotteryStatus::AwaitingRandomness {
+                let fulfillment_deadline_slot = lottery_data
+                    .randomness_request_slot
+                    .saturating_add(VRF_FULFILLMENT_TIMEOUT_SLOTS);
+                if clock.slot <= fulfillment_deadline_slot {
+                    msg!("Lottery can be cancelled after slot {}", fulfillment_deadline_slot);
+                    return Err(LotteryError::CancelTooEarly.into());
+                }
+            }
+        } else {
+            Self::check_randomness_mode(&lottery_data, RandomnessMode::CommitReveal)?;
+            Self::check_status(&lottery_data, LotteryStatus::SalesClosed)?;
+
+            // Anyone can cancel once the seed can not be revealed anymore
+            let reveal_deadline_slot = lottery_data
+                .sales_close_slot
+                .saturating_add(SEED_REVEAL_WINDOW_SLOTS);
+            if clock.slot <= reveal_deadline_slot {
+                msg!("Lottery can be cancelled after slot {}", reveal_deadline_slot);
+                return Err(LotteryError::CancelTooEarly.into());
+            }
+        }
+        Self::transition_status(&mut lottery_data, LotteryStatus::Cancelled)?;
+        msg!("Lottery {} cancelled", lottery_data.lottery_id);
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_refund_ticket(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let ticket_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let user_wallet_account = next_account_info(accounts_iter)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if ticket_account.owner != program_id {
+            msg!("Ticket Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Cancelled)?;
+        Self::check_vault_account(program_id, lottery_data_account, vault_account, &lottery_data)?;
+
+        let (token_vault_account, payout_program_info) = if lottery_data.is_token_lottery() {
+            let token_vault_account = next_account_info(accounts_iter)?;
+            let spl_token_info = next_account_info(accounts_iter)?;
+            Self::check_token_vault_account(
+                program_id,
+                lottery_data_account,
+                token_vault_account,
+                &lottery_data,
+            )?;
+            (Some(token_vault_account), spl_token_info)
+        } else {
+            (None, system_program_info)
+        };
+
+        let (user_wallet_pk, price_paid) =
+            Self::unpack_ticket_refund(ticket_account, lottery_data_account.key, &lottery_data)?;
+        if lottery_data.is_token_lottery() {
+            Self::check_token_account(user_wallet_account, &user_wallet_pk, &lottery_data)?;
+        } else if user_wallet_pk != *user_wallet_account.key {
+            msg!("Bad user_wallet_pk in ticket data account");
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+
+        Self::mark_ticket_claimed(ticket_account)?;
+        lottery_data.prize_pool_amount = lottery_data
+            .prize_pool_amount
+            .checked_sub(price_paid)
+            .ok_or(LotteryError::Overflow)?;
+
+        msg!("Refund of ticket {}: {}", ticket_account.key, price_paid);
+        Self::transfer_from_vault(
+            lottery_data_account,
+            &lottery_data,
+            vault_account,
+            token_vault_account,
+            payout_program_info,
+            user_wallet_account,
+            price_paid,
+        )?;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_reward_winners(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let lottery_result_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let holding_wallet_account = next_account_info(accounts_iter)?;
+        let rewards_wallet_account = next_account_info(accounts_iter)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if lottery_result_account.owner != program_id {
+            msg!("Lottery Result Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Drawn)?;
+
+        Self::check_vault_account(program_id, lottery_data_account, vault_account, &lottery_data)?;
+
+        // SPL token lottery pays from the token vault by SPL Token program
+        let (token_vault_account, payout_program_info) = if lottery_data.is_token_lottery() {
+            let token_vault_account = next_account_info(accounts_iter)?;
+            let spl_token_info = next_account_info(accounts_iter)?;
+            Self::check_token_vault_account(
+                program_id,
+                lottery_data_account,
+                token_vault_account,
+                &lottery_data,
+            )?;
+            (Some(token_vault_account), spl_token_info)
+        } else {
+            (None, system_program_info)
+        };
+        let participants_accounts = accounts_iter.as_slice();
+
+        if participants_accounts.len() % 2 != 0 {
+            msg!(
+                "Invalid participants accounts size: {}",
+                participants_accounts.len()
+            );
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+
+        // Count winners of all tickets in purchase order
+        for i in (0..participants_accounts.len()).step_by(2) {
+            let user_wallet_pk = Self::settle_ticket(
+                program_id,
+                lottery_data_account.key,
+                &mut lottery_data,
+                &participants_accounts[i],
+            )?;
+
+            if lottery_data.is_token_lottery() {
+                Self::check_token_account(
+                    &participants_accounts[i + 1],
+                    &user_wallet_pk,
+                    &lottery_data,
+                )?;
+            } else if user_wallet_pk != *participants_accounts[i + 1].key {
+                msg!("Bad user_wallet_pk in ticket data account");
+                return Err(LotteryError::InvalidParticipantsAccounts.into());
+            }
+        }
+
+        if lottery_data.settle_cursor != lottery_data.total_registrations {
+            msg!(
+                "Invalid participants lines count: {}",
+                lottery_data.settle_cursor
+            );
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+        msg!("Participants lines count: {}", lottery_data.settle_cursor);
+
+        Self::settle_prizes(
+            lottery_data_account,
+            lottery_data,
+            lottery_result_account,
+            vault_account,
+            holding_wallet_account,
+            rewards_wallet_account,
+            token_vault_account,
+            payout_program_info,
+        )
+    }
+
+    pub fn process_settle_batch(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let tickets_accounts = accounts_iter.as_slice();
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+
+        // First batch starts settlement
+        if lottery_data.status == LotteryStatus::Drawn {
+            Self::transition_status(&mut lottery_data, LotteryStatus::Settling)?;
+        }
+        Self::check_status(&lottery_data, LotteryStatus::Settling)?;
+
+        for ticket_account in tickets_accounts {
+            Self::settle_ticket(
+                program_id,
+                lottery_data_account.key,
+                &mut lottery_data,
+                ticket_account,
+            )?;
+        }
+
+        if lottery_data.settle_cursor > lottery_data.total_registrations {
+            msg!("Settlement cursor exceeds total registrations");
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+        msg!(
+            "Settled lines: {} of {}",
+            lottery_data.settle_cursor,
+            lottery_data.total_registrations
+        );
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_finalize_settlement(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let lottery_result_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let holding_wallet_account = next_account_info(accounts_iter)?;
+        let rewards_wallet_account = next_account_info(accounts_iter)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if lottery_result_account.owner != program_id {
+            msg!("Lottery Result Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Settling)?;
+
+        if lottery_data.settle_cursor != lottery_data.total_registrations {
+            msg!(
+                "Settled lines: {} of {}",
+                lottery_data.settle_cursor,
+                lottery_data.total_registrations
+            );
+            return Err(LotteryError::SettlementIncomplete.into());
+        }
+
+        Self::check_vault_account(program_id, lottery_data_account, vault_account, &lottery_data)?;
+
+        let (token_vault_account, payout_program_info) = if lottery_data.is_token_lottery() {
+            let token_vault_account = next_account_info(accounts_iter)?;
+            let spl_token_info = next_account_info(accounts_iter)?;
+            Self::check_token_vault_account(
+                program_id,
+                lottery_data_account,
+                token_vault_account,
+                &lottery_data,
+            )?;
+            (Some(token_vault_account), spl_token_info)
+        } else {
+            (None, system_program_info)
+        };
+
+        Self::settle_prizes(
+            lottery_data_account,
+            lottery_data,
+            lottery_result_account,
+            vault_account,
+            holding_wallet_account,
+            rewards_wallet_account,
+            token_vault_account,
+            payout_program_info,
+        )
+    }
+
+    /// Counts winning lines of the ticket at settlement cursor and moves the cursor,
+    /// returns user wallet of the ticket
+    fn settle_ticket(
+        program_id: &Pubkey,
+        lottery_key: &Pubkey,
+        lottery_data: &mut LotteryData,
+        ticket_account: &AccountInfo,
+    ) -> Result<Pubkey, ProgramError> {
+        if ticket_account.owner != program_id {
+            msg!("Ticket Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // Only tickets of this lottery in registration order count towards tier winners
+        let (user_wallet_pk, ticket_index, lines) =
+            Self::unpack_ticket_lines(ticket_account, lottery_key, lottery_data)?;
+        if ticket_index != lottery_data.settle_cursor {
+            msg!(
+                "Ticket index {} does not match settlement cursor {}",
+                ticket_index,
+                lottery_data.settle_cursor
+            );
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+
+        for ticket_number_arr in lines.iter() {
+            let (main_matches, bonus_match) = count_matches(
+                &lottery_data.game_matrix,
+                ticket_number_arr,
+                &lottery_data.winning_numbers,
+            );
+            if let Some(tier) = lottery_data.prize_table.tier_index(main_matches, bonus_match) {
+                msg!(
+                    "Found tier {}+{} {}",
+                    main_matches,
+                    bonus_match as u8,
+                    user_wallet_pk
+                );
+                lottery_data.tier_winners[tier] = lottery_data.tier_winners[tier]
+                    .checked_add(1)
+                    .ok_or(LotteryError::Overflow)?;
+            }
+        }
+        lottery_data.settle_cursor = lottery_data
+            .settle_cursor
+            .checked_add(lines.len() as u32)
+            .ok_or(LotteryError::Overflow)?;
+
+        Ok(user_wallet_pk)
+    }
+
+    /// Pays sollotto reward, computes prize of every tier from counted winners,
+    /// sweeps the rest of prize pool to holding wallet and moves lottery to `Settled`
+    fn settle_prizes<'b>(
+        lottery_data_account: &AccountInfo<'b>,
+        mut lottery_data: LotteryData,
+        lottery_result_account: &AccountInfo<'b>,
+        vault_account: &AccountInfo<'b>,
+        holding_wallet_account: &AccountInfo<'b>,
+        rewards_wallet_account: &AccountInfo<'b>,
+        token_vault_account: Option<&AccountInfo<'b>>,
+        payout_program_info: &AccountInfo<'b>,
+    ) -> ProgramResult {
+        Self::transition_status(&mut lottery_data, LotteryStatus::Settled)?;
+        Self::check_sales_snapshot(&lottery_data)?;
+
+        // Check all sollotto keys
+        if lottery_data.is_token_lottery() {
+            Self::check_token_account(
+                holding_wallet_account,
+                &lottery_data.holding_wallet,
+                &lottery_data,
+            )?;
+            Self::check_token_account(
+                rewards_wallet_account,
+                &lottery_data.rewards_wallet,
+                &lottery_data,
+            )?;
+        } else {
+            if *holding_wallet_account.key != lottery_data.holding_wallet {
+                return Err(LotteryError::InvalidSollottoAccount.into());
+            }
+            if *rewards_wallet_account.key != lottery_data.rewards_wallet {
+                return Err(LotteryError::InvalidSollottoAccount.into());
+            }
+        }
+
+        let vault_balance = match token_vault_account {
+            Some(token_vault_account) => {
+                TokenAccount::unpack(&token_vault_account.data.borrow())?.amount
+            }
+            None => vault_account
+                .lamports()
+                .saturating_sub(lottery_data.vault_rent_reserve),
+        };
+        if vault_balance < lottery_data.prize_pool_amount {
+            msg!("Prize vault InsufficientFunds error");
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        msg!("Prize pool in lamports: {}", lottery_data.prize_pool_amount);
+        let split = split_prize_pool(
+            lottery_data.prize_pool_amount,
+            &lottery_data.prize_table,
+            &lottery_data.tier_winners,
+        )
+        .ok_or(LotteryError::Overflow)?;
+        let distributed = split
+            .sollotto_reward
+            .checked_add(split.reserved)
+            .and_then(|amount| amount.checked_add(split.sweep))
+            .and_then(|amount| amount.checked_add(split.dust))
+            .ok_or(LotteryError::Overflow)?;
+        if distributed != lottery_data.prize_pool_amount {
+            msg!("Prize split does not match prize pool");
+            return Err(LotteryError::Overflow.into());
+        }
+
+        // 7. 5% of the prize pool is transferred to the "Avalor" wallet address
+        msg!("Solloto reward in lamports: {}", split.sollotto_reward);
+        // Transfer from prize vault to solloto_rewards_wallet
+        Self::transfer_from_vault(
+            lottery_data_account,
+            &lottery_data,
+            vault_account,
+            token_vault_account,
+            payout_program_info,
+            rewards_wallet_account,
+            split.sollotto_reward,
+        )?;
+
+        // Process rewards, winners claim them by ClaimPrize
+        for ((tier, winners_count), reward) in lottery_data
+            .prize_table
+            .tiers()
+            .iter()
+            .zip(lottery_data.tier_winners.iter())
+            .zip(split.tier_payouts.iter())
+        {
+            msg!(
+                "Winners(tier {}+{}) number {}",
+                tier.main_matches,
+                tier.bonus_match as u8,
+                winners_count
+            );
+            msg!("Winner reward in lamports: {}", reward);
+        }
+        lottery_data.tier_payouts = split.tier_payouts;
+
+        // Sweep pools of tiers without winners from prize vault to holding wallet
+        msg!("Sweep to holding wallet: {}", split.sweep);
+        Self::transfer_from_vault(
+            lottery_data_account,
+            &lottery_data,
+            vault_account,
+            token_vault_account,
+            payout_program_info,
+            holding_wallet_account,
+            split.sweep,
+        )?;
+
+        // Keep claimable prizes and dust in prize vault, dust is rolled to the next draw
+        msg!("Rollover dust: {}", split.dust);
+        lottery_data.prize_pool_amount = split.reserved;
+        lottery_data.rollover_amount = split.dust;
+
+        // Create lottery result acc info
+        let lottery_result = LotteryResultData {
+            lottery_id: lottery_data.lottery_id,
+            winning_numbers: lottery_data.winning_numbers,
+            game_matrix: lottery_data.game_matrix,
+            draw_proof: lottery_data.draw_proof,
+            ticket_ledger_hash: lottery_data.ticket_ledger_hash,
+            seed_commitment: lottery_data.seed_commitment,
+        };
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+        LotteryResultData::pack(
+            lottery_result,
+            &mut lottery_result_account.data.borrow_mut(),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn process_claim_prize(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let ticket_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+        let user_wallet_account = next_account_info(accounts_iter)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if ticket_account.owner != program_id {
+            msg!("Ticket Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Settled)?;
+        Self::check_vault_account(program_id, lottery_data_account, vault_account, &lottery_data)?;
+
+        let (token_vault_account, payout_program_info) = if lottery_data.is_token_lottery() {
+            let token_vault_account = next_account_info(accounts_iter)?;
+            let spl_token_info = next_account_info(accounts_iter)?;
+            Self::check_token_vault_account(
+                program_id,
+                lottery_data_account,
+                token_vault_account,
+                &lottery_data,
+            )?;
+            (Some(token_vault_account), spl_token_info)
+        } else {
+            (None, system_program_info)
+        };
+
+        // Anyone can claim, only tickets purchased in this lottery are paid from its vault
+        let (user_wallet_pk, _, lines) =
+            Self::unpack_ticket_lines(ticket_account, lottery_data_account.key, &lottery_data)?;
+        if lottery_data.is_token_lottery() {
+            Self::check_token_account(user_wallet_account, &user_wallet_pk, &lottery_data)?;
+        } else if user_wallet_pk != *user_wallet_account.key {
+            msg!("Bad user_wallet_pk in ticket data account");
+            return Err(LotteryError::InvalidParticipantsAccounts.into());
+        }
+
+        let mut prize: u64 = 0;
+        for ticket_number_arr in lines {
+            let (main_matches, bonus_match) = count_matches(
+                &lottery_data.game_matrix,
+                &ticket_number_arr,
+                &lottery_data.winning_numbers,
+            );
+            if let Some(tier) = lottery_data.prize_table.tier_index(main_matches, bonus_match) {
+                prize = prize
+                    .checked_add(lottery_data.tier_payouts[tier])
+                    .ok_or(LotteryError::Overflow)?;
+            }
+        }
+        if prize == 0 {
+            msg!("Ticket {} has no prize", ticket_account.key);
+            return Err(LotteryError::NoPrize.into());
+        }
+
+        Self::mark_ticket_claimed(ticket_account)?;
+        lottery_data.prize_pool_amount = lottery_data
+            .prize_pool_amount
+            .checked_sub(prize)
+            .ok_or(LotteryError::Overflow)?;
+
+        msg!("Prize of ticket {}: {}", ticket_account.key, prize);
+        Self::transfer_from_vault(
+            lottery_data_account,
+            &lottery_data,
+            vault_account,
+            token_vault_account,
+            payout_program_info,
+            user_wallet_account,
+            prize,
+        )?;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_assign_quick_picks(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let tickets_accounts = accounts_iter.as_slice();
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Drawn)?;
+
+        for ticket_account in tickets_accounts {
+            if ticket_account.owner != program_id {
+                msg!("Ticket Data account does not have the correct program id");
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            let mut ticket_data = TicketData::unpack_unchecked(&ticket_account.data.borrow())?;
+            if !ticket_data.is_purchased {
+                msg!("Ticket data account is not purchased");
+                return Err(LotteryError::NotInitialized.into());
+            }
+            Self::check_ticket_lottery(
+                ticket_account,
+                &ticket_data.lottery,
+                ticket_data.lottery_id,
+                lottery_data_account.key,
+                &lottery_data,
+            )?;
+            if !ticket_data.is_quick_pick_pending() {
+                msg!("Ticket {} is not a pending quick pick", ticket_account.key);
+                return Err(LotteryError::InvalidParticipantsAccounts.into());
+            }
+
+            let ticket_number_arr = quick_pick_numbers(
+                &lottery_data.game_matrix,
+                &lottery_data.draw_seed,
+                ticket_account.key,
+            )
+            .ok_or(LotteryError::InvalidRandomResult)?;
+            if !lottery_data
+                .game_matrix
+                .is_valid_combination(&ticket_number_arr)
+            {
+                msg!("Quick pick numbers do not match game matrix");
+                return Err(LotteryError::InvalidNumber.into());
+            }
+
+            ticket_data.ticket_number_arr = ticket_number_arr;
+            ticket_data.quick_pick_assigned = true;
+            TicketData::pack(ticket_data, &mut ticket_account.data.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process_update_sollotto_wallets(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        holding_wallet: Pubkey,
+        rewards_wallet: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+
+        lottery_data.holding_wallet = holding_wallet;
+        lottery_data.rewards_wallet = rewards_wallet;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+    pub fn process_update_ticket_price(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        ticket_price_lamports: u64,
+        ticket_price_usd_cents: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let lottery_data_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let clock = Clock::from_account_info(next_account_info(accounts_iter)?)?;
+
+        if lottery_data_account.owner != program_id {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut lottery_data = LotteryData::unpack_unchecked(&lottery_data_account.data.borrow())?;
+        if !lottery_data.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, lottery_data_account, &lottery_data)?;
+        Self::check_lottery_authority(lottery_authority_account, &lottery_data)?;
+        Self::check_status(&lottery_data, LotteryStatus::Open)?;
+
+        if clock.unix_timestamp >= lottery_data.sales_open_ts {
+            msg!("Ticket price cannot be changed after ticket sales opened");
+            return Err(LotteryError::SalesStarted.into());
+        }
+        if ticket_price_lamports == 0 && ticket_price_usd_cents == 0 {
+            msg!("Ticket price must be greater than zero");
+            return Err(LotteryError::InvalidTicketPrice.into());
+        }
+        if lottery_data.is_token_lottery() && ticket_price_usd_cents != 0 {
+            msg!("USD ticket price is not supported for SPL token lottery");
+            return Err(LotteryError::InvalidTicketPrice.into());
+        }
+        if ticket_price_usd_cents != 0 && lottery_data.price_feed == Pubkey::default() {
+            msg!("USD ticket price requires a price feed");
+            return Err(LotteryError::InvalidPriceFeed.into());
+        }
+
+        lottery_data.ticket_price_lamports = ticket_price_lamports;
+        lottery_data.ticket_price_usd_cents = ticket_price_usd_cents;
+
+        LotteryData::pack(lottery_data, &mut lottery_data_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn process_rollover_prize_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let settled_lottery_account = next_account_info(accounts_iter)?;
+        let lottery_authority_account = next_account_info(accounts_iter)?;
+        let settled_vault_account = next_account_info(accounts_iter)?;
+        let next_lottery_account = next_account_info(accounts_iter)?;
+        let next_vault_account = next_account_info(accounts_iter)?;
+        let system_program_info = next_account_info(accounts_iter)?;
+
+        if settled_lottery_account.owner != program_id || next_lottery_account.owner != program_id
+        {
+            msg!("Lottery Data account does not have the correct program id");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if settled_lottery_account.key == next_lottery_account.key {
+            msg!("Prize pool cannot be rolled over to the same lottery");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut settled_lottery =
+            LotteryData::unpack_unchecked(&settled_lottery_account.data.borrow())?;
+        let mut next_lottery = LotteryData::unpack_unchecked(&next_lottery_account.data.borrow())?;
+        if !settled_lottery.is_initialized || !next_lottery.is_initialized {
+            msg!("Lottery Data account is not initialized");
+            return Err(LotteryError::NotInitialized.into());
+        }
+        Self::check_lottery_account(program_id, settled_lottery_account, &settled_lottery)?;
+        Self::check_lottery_account(program_id, next_lottery_account, &next_lottery)?;
+        Self::check_lottery_authority(lottery_authority_account, &settled_lottery)?;
+        Self::check_lottery_authority(lottery_authority_account, &next_lottery)?;
+        Self::check_status(&settled_lottery, LotteryStatus::Settled)?;
+        Self::check_status(&next_lottery, LotteryStatus::Open)?;
+        if settled_lottery.token_mint != next_lottery.token_mint {
+            msg!("Lotteries have different token mints");
+            return Err(LotteryError::InvalidTokenAccount.into());
+        }
+
+        Self::check_vault_account(
+            program_id,
+            settled_lottery_account,
+            settled_vault_account,
+            &settled_lottery,
+        )?;
+        Self::check_vault_account(
+            program_id,
+            next_lottery_account,
+            next_vault_account,
+            &next_lottery,
+        )?;
+
+        // SPL token lottery dust is moved between token vaults
+        let (settled_token_vault_account, destination_account, payout_program_info) =
+            if settled_lottery.is_token_lottery() {
+                let settled_token_vault_account = next_account_info(accounts_iter)?;
+                let next_token_vault_account = next_account_info(accounts_iter)?;
+                let spl_token_info = next_account_info(accounts_iter)?;
+                Self::check_token_vault_account(
+                    program_id,
+                    settled_lottery_account,
+                    settled_token_vault_account,
+                    &settled_lottery,
+                )?;
+                Self::check_token_vault_account(
+                    program_id,
+                    next_lottery_account,
+                    next_token_vault_account,
+                    &next_lottery,
+                )?;
+                (
+                    Some(settled_token_vault_account),
+                    next_token_vault_account,
+                    spl_token_info,
+                )
+            } else {
+                (None, next_vault_account, system_program_info)
+            };
+
+        let rollover_amount = settled_lottery.rollover_amount;
+        msg!("Rollover to the next draw: {}", rollover_amount);
+        next_lottery.prize_pool_amount = next_lottery
+            .prize_pool_amount
+            .checked_add(rollover_amount)
+            .ok_or(LotteryError::Overflow)?;
+        settled_lottery.rollover_amount = 0;
+
+        Self::transfer_from_vault(
+            settled_lottery_account,
+            &settled_lottery,
+            settled_vault_account,
+            settled_token_vault_account,
+            payout_program_info,
+            destination_account,
+            rollover_amount,
+        )?;
+
+        LotteryData::pack(settled_lottery, &mut settled_lottery_account.data.borrow_mut())?;
+        LotteryData::pack(next_lottery, &mut next_lottery_account.data.borrow_mut())?;
+
+        Ok(())
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod test {
+    use super::*;
+    use solana_program::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        native_token::sol_to_lamports,
+        program_pack::Pack,
+        slot_hashes::SlotHashes,
+        sysvar::recent_blockhashes,
+    };
+    use solana_sdk::account::{
+        create_account_for_test, create_is_signer_account_infos, Account as SolanaAccount,
+        ReadableAccount,
+    };
+    use crate::randomness::{check_vrf_round, VrfRound};
+    use crate::state::PrizeTier;
+    use spl_token::state::Account;
+
+    const SALES_OPEN_TS: i64 = 0;
+    const SALES_CLOSE_TS: i64 = 100;
+    const DRAW_AFTER_TS: i64 = 200;
+
+    fn lottery_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(LotteryData::get_packed_len())
+    }
+
+    fn ticket_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(TicketData::get_packed_len())
+    }
+
+    fn lottery_result_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(LotteryResultData::get_packed_len())
+    }
+
+    fn mint_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(spl_token::state::Mint::LEN)
+    }
+
+    fn account_minimum_balance() -> u64 {
+        Rent::default().minimum_balance(spl_token::state::Account::LEN)
+    }
+
+    fn test_game_matrix() -> GameMatrix {
+        GameMatrix {
+            main_count: 5,
+            main_range: 69,
+            bonus_count: 1,
+            bonus_range: 29,
+            kind: GameKind::Balls,
+        }
+    }
+
+    fn test_prize_table() -> PrizeTable {
+        PrizeTable::new(&[
+            PrizeTier {
+                main_matches: 3,
+                bonus_match: false,
+                kind: PrizeKind::Fixed,
+                amount: sol_to_lamports(0.1),
+            },
+            PrizeTier {
+                main_matches: 4,
+                bonus_match: false,
+                kind: PrizeKind::PoolShare,
+                amount: 500,
+            },
+            PrizeTier {
+                main_matches: 5,
+                bonus_match: false,
+                kind: PrizeKind::PoolShare,
+                amount: 500,
+            },
+            PrizeTier {
+                main_matches: 5,
+                bonus_match: true,
+                kind: PrizeKind::PoolShare,
+                amount: 8_500,
+            },
+        ])
+        .unwrap()
+    }
+
+    fn test_lottery_config() -> LotteryConfig {
+        LotteryConfig {
+            holding_wallet: Pubkey::new_unique(),
+            rewards_wallet: Pubkey::new_unique(),
+            randomness_account: Pubkey::new_unique(),
+            randomness_mode: RandomnessMode::SwitchboardVrf,
+            seed_commitment: [0; 32],
+            sales_open_ts: SALES_OPEN_TS,
+            sales_close_ts: SALES_CLOSE_TS,
+            draw_after_ts: DRAW_AFTER_TS,
+            game_matrix: test_game_matrix(),
+            prize_table: test_prize_table(),
+            ticket_price_lamports: sol_to_lamports(0.1),
+            ticket_price_usd_cents: 0,
+            price_feed: Pubkey::default(),
+            token_mint: Pubkey::default(),
+        }
+    }
+
+    fn do_process(instruction: Instruction, accounts: Vec<&mut SolanaAccount>) -> ProgramResult {
+        let mut meta = instruction
+            .accounts
+            .iter()
+            .zip(accounts)
+            .map(|(account_meta, account)| (&account_meta.pubkey, account_meta.is_signer, account))
+            .collect::<Vec<_>>();
+
+        let account_infos = create_is_signer_account_infos(&mut meta);
+        Processor::process(&instruction.program_id, &account_infos, &instruction.data)
+    }
+
+    #[test]
+    fn test_init_lottery() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (_, bump_seed) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+
+        // BadCase: lottery account is not the program address
+        let mut instruction = crate::instruction::initialize_lottery(
+            &program_id,
+            lottery_id,
+            &LotteryConfig {
+                holding_wallet,
+                rewards_wallet,
+                randomness_account,
+                game_matrix,
+                ticket_price_lamports: ticket_price,
+                ..test_lottery_config()
+            },
+            &lottery_authority,
+        )
+        .unwrap();
+        instruction.accounts[0].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(LotteryError::InvalidLotteryAccount.into()),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: game matrix does not fit into ticket
+        assert_eq!(
+            Err(LotteryError::InvalidGameMatrix.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix: GameMatrix {
+                            main_count: 6,
+                            main_range: 49,
+                            bonus_count: 1,
+                            bonus_range: 26,
+                            kind: GameKind::Balls,
+                        },
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: prize table pool shares exceed prize pool
+        let mut bad_prize_table = test_prize_table();
+        bad_prize_table.tiers[3].amount = 9_001;
+        assert_eq!(
+            Err(LotteryError::InvalidPrizeTable.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        prize_table: bad_prize_table,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: prize table pool shares overflow with sollotto reward
+        let mut bad_prize_table = test_prize_table();
+        bad_prize_table.tiers[3].amount = u64::MAX
+            - (test_prize_table().pool_share_bps().unwrap() - test_prize_table().tiers[3].amount);
+        assert_eq!(
+            Err(LotteryError::InvalidPrizeTable.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        prize_table: bad_prize_table,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: sales close after draw time
+        assert_eq!(
+            Err(LotteryError::InvalidSalesSchedule.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        sales_close_ts: DRAW_AFTER_TS + 1,
+                        game_matrix,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: USD ticket price without price feed
+        assert_eq!(
+            Err(LotteryError::InvalidPriceFeed.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        ticket_price_lamports: 0,
+                        ticket_price_usd_cents: 500,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: prize vault is not the lottery vault program address
+        let mut instruction = crate::instruction::initialize_lottery(
+            &program_id,
+            lottery_id,
+            &LotteryConfig {
+                holding_wallet,
+                rewards_wallet,
+                randomness_account,
+                game_matrix,
+                ticket_price_lamports: ticket_price,
+                ..test_lottery_config()
+            },
+            &lottery_authority,
+        )
+        .unwrap();
+        instruction.accounts[4].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(LotteryError::InvalidVaultAccount.into()),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        // BadCase: rent NotRentExempt
+        let mut bad_lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance() - 100,
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        assert_eq!(
+            Err(LotteryError::NotRentExempt.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut bad_lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: Lottery Already initialized
+        assert_eq!(
+            Err(LotteryError::Initialized.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.is_initialized, true);
+        assert_eq!(lottery.status, LotteryStatus::Open);
+        assert_eq!(lottery.lottery_id, lottery_id);
+        
+        assert_eq!(lottery.holding_wallet, holding_wallet);
+        assert_eq!(lottery.rewards_wallet, rewards_wallet);
+        assert_eq!(lottery.game_matrix, game_matrix);
+        assert_eq!(lottery.prize_table, test_prize_table());
+        assert_eq!(lottery.sales_open_ts, SALES_OPEN_TS);
+        assert_eq!(lottery.sales_close_ts, SALES_CLOSE_TS);
+        assert_eq!(lottery.draw_after_ts, DRAW_AFTER_TS);
+        assert_eq!(lottery.ticket_price_lamports, ticket_price);
+        assert_eq!(lottery.authority, lottery_authority);
+        assert_eq!(lottery.bump_seed, bump_seed);
+        assert_eq!(lottery.total_registrations, 0);
+        assert_eq!(lottery.prize_pool_amount, 0);
+        assert_eq!(lottery.vault_rent_reserve, Rent::default().minimum_balance(0));
+        for number in &lottery.winning_numbers {
+            assert_eq!(*number, 0);
+        }
+    }
+
+    #[test]
+    fn test_ticket_purchase() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let user_funding_key = Pubkey::new_unique();
+        let mut user_funding_acc = SolanaAccount::default();
+        let user_ticket_key = Pubkey::new_unique();
+        let mut user_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut spl_token_acc = SolanaAccount::default();
+        
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let mut vault_acc = SolanaAccount::default();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+        let user_charity = Pubkey::new_unique();
+
+        let user_lifetime_ticket_key = Pubkey::new_unique();
+        let mut user_lifetime_ticket_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        let lifetime_ticket_mint_key = Pubkey::new_unique();
+        let mut lifetime_ticket_mint_acc =
+            SolanaAccount::new(mint_minimum_balance(), Mint::LEN, &spl_token::id());
+        let lifetime_ticket_owner_key = Pubkey::new_unique();
+        let mut lifetime_ticket_owner_acc = SolanaAccount::default();
+
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut lifetime_ticket_mint_acc.data,
+        )
+        .unwrap();
+
+        // BadCase: Lottery is not initialized
+        assert_eq!(
+            Err(LotteryError::NotInitialized.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: prize vault is not the lottery vault
+        let mut instruction = crate::instruction::purchase_ticket(
+            &program_id,
+            &user_charity,
+            &user_funding_key,
+            &[10, 20, 30, 40, 50, 29],
+            ticket_price,
+            false,
+            &user_ticket_key,
+            &lottery_key,
+            &user_lifetime_ticket_key,
+            &lifetime_ticket_owner_key,
+            &lifetime_ticket_mint_key,
+            None,
+            None,
+        )
+        .unwrap();
+        instruction.accounts[3].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(LotteryError::InvalidVaultAccount.into()),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: user cannot pay
+        assert_eq!(
+            Err(ProgramError::InsufficientFunds),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 29],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        user_funding_acc.lamports += sol_to_lamports(0.1);
+
+        // BadCase: ticket price exceeds max price
+        assert_eq!(
+            Err(LotteryError::PriceExceedsMax.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price - 1,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: ticket sales are not open yet
+        let mut early_clock_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_OPEN_TS - 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::SalesNotOpen.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut early_clock_acc,
+                ]
+            )
+        );
+
+        // BadCase: ticket sales are over
+        let mut late_clock_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_CLOSE_TS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::SalesClosed.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut late_clock_acc,
+                ]
+            )
+        );
+
+        // BadCase: rent NotRentExempt
+        let mut bad_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance() - 100,
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+        assert_eq!(
+            Err(LotteryError::NotRentExempt.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut bad_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: duplicated main ball
+        assert_eq!(
+            Err(LotteryError::InvalidNumber.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[20, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: bad numbers
+        assert_eq!(
+            Err(LotteryError::InvalidNumber.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[70, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        assert_eq!(
+            Err(LotteryError::InvalidNumber.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 0, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        assert_eq!(
+            Err(LotteryError::InvalidNumber.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 30],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::purchase_ticket(
+                &program_id,
+                &user_charity,
+                &user_funding_key,
+                &[10, 20, 30, 40, 50, 29],
+                ticket_price,
+                false,
+                &user_ticket_key,
+                &lottery_key,
+                &user_lifetime_ticket_key,
+                &lifetime_ticket_owner_key,
+                &lifetime_ticket_mint_key,
+                None,
+                None,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user_ticket_acc,
+                &mut user_funding_acc,
+                &mut vault_acc,
+                &mut user_lifetime_ticket_acc,
+                &mut lifetime_ticket_owner_acc,
+                &mut lifetime_ticket_mint_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut spl_token_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data()).unwrap();
+        assert_eq!(lottery.total_registrations, 1);
+        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.1));
+        assert_eq!(
+            lottery.ticket_ledger_hash,
+            next_ticket_ledger_hash(
+                &[0; 32],
+                &user_ticket_key,
+                &user_funding_key,
+                &[[10, 20, 30, 40, 50, 29]],
+                0,
+            )
+        );
+        let ticket = TicketData::unpack_unchecked(&user_ticket_acc.data).unwrap();
+        assert_eq!(ticket.lottery, lottery_key);
+        assert_eq!(ticket.lottery_id, lottery_id);
+        assert_eq!(ticket.price_paid, ticket_price);
+
+        // BadCase: Ticket already purchased
+        assert_eq!(
+            Err(LotteryError::AlreadyPurchased.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 30],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: total registrations overflow
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.total_registrations = u32::MAX;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+        let overflow_ticket_key = Pubkey::new_unique();
+        let mut overflow_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+        assert_eq!(
+            Err(LotteryError::Overflow.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 29],
+                    ticket_price,
+                    false,
+                    &overflow_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut overflow_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+        lottery.total_registrations = 1;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+
+        // BadCase: sales close time is not reached
+        let mut randomness_acc = SolanaAccount::default();
+        assert_eq!(
+            Err(LotteryError::SalesCloseTooEarly.into()),
+            do_process(
+                crate::instruction::close_sales(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    Some(&randomness_account),
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut clock_sysvar_acc,
+                    &mut randomness_acc,
+                ]
+            )
+        );
+
+        let mut close_clock_sysvar_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_CLOSE_TS,
+            ..Default::default()
+        });
+
+        do_process(
+            crate::instruction::close_sales(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                Some(&randomness_account),
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut close_clock_sysvar_acc,
+                &mut randomness_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::SalesClosed);
+        assert_eq!(lottery.closed_registrations, 1);
+        assert_eq!(lottery.closed_prize_pool, sol_to_lamports(0.1));
+
+        let user_funding_key = Pubkey::new_unique();
+        let mut user_funding_acc = SolanaAccount::default();
+        let user_ticket_key = Pubkey::new_unique();
+        let mut user_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+
+        // BadCase: sales are closed
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 30],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_token_lottery_purchase() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let user_funding_key = Pubkey::new_unique();
+        let mut user_funding_acc = SolanaAccount::default();
+        let user_ticket_key = Pubkey::new_unique();
+        let mut user_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut spl_token_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let mut vault_acc = SolanaAccount::default();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = 5_000_000;
+        let user_charity = Pubkey::new_unique();
+
+        let user_lifetime_ticket_key = Pubkey::new_unique();
+        let mut user_lifetime_ticket_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        let lifetime_ticket_mint_key = Pubkey::new_unique();
+        let mut lifetime_ticket_mint_acc =
+            SolanaAccount::new(mint_minimum_balance(), Mint::LEN, &spl_token::id());
+        let lifetime_ticket_owner_key = Pubkey::new_unique();
+        let mut lifetime_ticket_owner_acc = SolanaAccount::default();
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut lifetime_ticket_mint_acc.data,
+        )
+        .unwrap();
+
+        let token_mint_key = Pubkey::new_unique();
+        let mut token_mint_acc =
+            SolanaAccount::new(mint_minimum_balance(), Mint::LEN, &spl_token::id());
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                decimals: 6,
+                ..Default::default()
+            },
+            &mut token_mint_acc.data,
+        )
+        .unwrap();
+        let (vault_key, _) = find_vault_address(&program_id, &lottery_key);
+        let mut token_vault_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        Account::pack(
+            Account {
+                mint: token_mint_key,
+                owner: vault_key,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut token_vault_acc.data,
+        )
+        .unwrap();
+        let user_token_key = Pubkey::new_unique();
+        let mut user_token_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        Account::pack(
+            Account {
+                mint: token_mint_key,
+                owner: user_funding_key,
+                amount: ticket_price,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut user_token_acc.data,
+        )
+        .unwrap();
+
+        // BadCase: USD ticket price for token lottery
+        assert_eq!(
+            Err(LotteryError::InvalidTicketPrice.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        ticket_price_lamports: ticket_price,
+                        ticket_price_usd_cents: 500,
+                        token_mint: token_mint_key,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                    &mut token_mint_acc,
+                    &mut token_vault_acc,
+                    &mut spl_token_acc,
+                ],
+            )
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    token_mint: token_mint_key,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+                &mut token_mint_acc,
+                &mut token_vault_acc,
+                &mut spl_token_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert!(lottery.is_token_lottery());
+        assert_eq!(lottery.token_mint, token_mint_key);
+
+        // BadCase: user token account of another mint
+        let mut bad_user_token_acc = user_token_acc.clone();
+        let mut bad_token_account = Account::unpack(&bad_user_token_acc.data).unwrap();
+        bad_token_account.mint = Pubkey::new_unique();
+        Account::pack(bad_token_account, &mut bad_user_token_acc.data).unwrap();
+        assert_eq!(
+            Err(LotteryError::InvalidTokenAccount.into()),
+            do_process(
+                crate::instruction::purchase_ticket(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[10, 20, 30, 40, 50, 15],
+                    ticket_price,
+                    false,
+                    &user_ticket_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    Some(&user_token_key),
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                    &mut bad_user_token_acc,
+                    &mut token_vault_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::purchase_ticket(
+                &program_id,
+                &user_charity,
+                &user_funding_key,
+                &[10, 20, 30, 40, 50, 15],
+                ticket_price,
+                false,
+                &user_ticket_key,
+                &lottery_key,
+                &user_lifetime_ticket_key,
+                &lifetime_ticket_owner_key,
+                &lifetime_ticket_mint_key,
+                None,
+                Some(&user_token_key),
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user_ticket_acc,
+                &mut user_funding_acc,
+                &mut vault_acc,
+                &mut user_lifetime_ticket_acc,
+                &mut lifetime_ticket_owner_acc,
+                &mut lifetime_ticket_mint_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut spl_token_acc,
+                &mut clock_sysvar_acc,
+                &mut user_token_acc,
+                &mut token_vault_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.total_registrations, 1);
+        assert_eq!(lottery.prize_pool_amount, ticket_price);
+    }
+
+    #[test]
+    fn test_tickets_purchase() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let user_funding_key = Pubkey::new_unique();
+        let mut user_funding_acc = SolanaAccount::default();
+        user_funding_acc.lamports = sol_to_lamports(1.0);
+        let user_ticket_book_key = Pubkey::new_unique();
+        let mut user_ticket_book_acc = SolanaAccount::new(
+            Rent::default().minimum_balance(TicketBookData::get_packed_len()),
+            TicketBookData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut spl_token_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let mut vault_acc = SolanaAccount::default();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+        let user_charity = Pubkey::new_unique();
+        let lines = [
+            [10, 20, 30, 40, 50, 15],
+            [1, 2, 3, 4, 5, 6],
+            [69, 68, 67, 66, 65, 29],
+        ];
+
+        let user_lifetime_ticket_key = Pubkey::new_unique();
+        let mut user_lifetime_ticket_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        let lifetime_ticket_mint_key = Pubkey::new_unique();
+        let mut lifetime_ticket_mint_acc =
+            SolanaAccount::new(mint_minimum_balance(), Mint::LEN, &spl_token::id());
+        let lifetime_ticket_owner_key = Pubkey::new_unique();
+        let mut lifetime_ticket_owner_acc = SolanaAccount::default();
+        Mint::pack(
+            Mint {
+                is_initialized: true,
+                ..Default::default()
+            },
+            &mut lifetime_ticket_mint_acc.data,
+        )
+        .unwrap();
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: too many lines
+        assert_eq!(
+            Err(LotteryError::InvalidTicketLinesCount.into()),
+            do_process(
+                crate::instruction::purchase_tickets(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &[[10, 20, 30, 40, 50, 15]; MAX_TICKET_BOOK_LINES + 1],
+                    ticket_price * (MAX_TICKET_BOOK_LINES as u64 + 1),
+                    &user_ticket_book_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_book_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: price of all lines exceeds max price
+        assert_eq!(
+            Err(LotteryError::PriceExceedsMax.into()),
+            do_process(
+                crate::instruction::purchase_tickets(
+                    &program_id,
+                    &user_charity,
+                    &user_funding_key,
+                    &lines,
+                    ticket_price * 2,
+                    &user_ticket_book_key,
+                    &lottery_key,
+                    &user_lifetime_ticket_key,
+                    &lifetime_ticket_owner_key,
+                    &lifetime_ticket_mint_key,
+                    None,
+                    None,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_book_acc,
+                    &mut user_funding_acc,
+                    &mut vault_acc,
+                    &mut user_lifetime_ticket_acc,
+                    &mut lifetime_ticket_owner_acc,
+                    &mut lifetime_ticket_mint_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut spl_token_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::purchase_tickets(
+                &program_id,
+                &user_charity,
+                &user_funding_key,
+                &lines,
+                ticket_price * 3,
+                &user_ticket_book_key,
+                &lottery_key,
+                &user_lifetime_ticket_key,
+                &lifetime_ticket_owner_key,
+                &lifetime_ticket_mint_key,
+                None,
+                None,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user_ticket_book_acc,
+                &mut user_funding_acc,
+                &mut vault_acc,
+                &mut user_lifetime_ticket_acc,
+                &mut lifetime_ticket_owner_acc,
+                &mut lifetime_ticket_mint_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut spl_token_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.total_registrations, 3);
+        assert_eq!(lottery.prize_pool_amount, ticket_price * 3);
+
+        let ticket_book = TicketBookData::unpack_unchecked(&user_ticket_book_acc.data).unwrap();
+        assert!(ticket_book.is_purchased);
+        assert_eq!(ticket_book.charity, user_charity);
+        assert_eq!(ticket_book.user_wallet_pk, user_funding_key);
+        assert_eq!(ticket_book.purchased_lines(), &lines[..]);
+    }
+
+    #[test]
+    fn test_store_winning_numbers() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+        let mut randomness_acc = SolanaAccount::default();
+
+        // BadCase: Lottery is not initialized
+        assert_eq!(
+            Err(LotteryError::NotInitialized.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: Invalid lottery authority
+        let fake_authority = Pubkey::new_unique();
+        let mut fake_authority_acc = SolanaAccount::default();
+        assert_eq!(
+            Err(LotteryError::InvalidAuthority.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &fake_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut fake_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: Lottery authority is not a signer
+        let mut instruction = crate::instruction::store_winning_numbers(
+            &program_id,
+            &lottery_key,
+            &lottery_authority,
+            &randomness_account,
+        )
+        .unwrap();
+        instruction.accounts[1].is_signer = false;
+        assert_eq!(
+            Err(ProgramError::MissingRequiredSignature),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: sales are not closed
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: sales are closed with another randomness account
+        let fake_randomness_account = Pubkey::new_unique();
+        let mut fake_randomness_acc = SolanaAccount::default();
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 42,
+            unix_timestamp: SALES_CLOSE_TS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessAccount.into()),
+            do_process(
+                crate::instruction::close_sales(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    Some(&fake_randomness_account),
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut clock_sysvar_acc,
+                    &mut fake_randomness_acc,
+                ]
+            )
+        );
+
+        // BadCase: Vrf account is missing for Switchboard lottery
+        assert_eq!(
+            Err(ProgramError::NotEnoughAccountKeys),
+            do_process(
+                crate::instruction::close_sales(&program_id, &lottery_key, &lottery_authority, None)
+                    .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::close_sales(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                Some(&randomness_account),
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut clock_sysvar_acc,
+                &mut randomness_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::SalesClosed);
+        assert_eq!(lottery.sales_close_slot, 42);
+        assert_eq!(lottery.close_vrf_counter, 0);
+
+        // BadCase: sales are already closed
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::close_sales(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    Some(&randomness_account),
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut clock_sysvar_acc,
+                    &mut randomness_acc,
+                ]
+            )
+        );
+
+        // BadCase: randomness is not requested
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: randomness is requested for another randomness account
+        let mut switchboard_acc = SolanaAccount::default();
+        let vrf_request_accounts = switchboard::VrfRequestAccounts::default();
+        let mut vrf_request_accs = vec![SolanaAccount::default(); 9];
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 43,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessAccount.into()),
+            do_process(
+                crate::instruction::request_randomness(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &fake_randomness_account,
+                    &vrf_request_accounts,
+                    1,
+                    2,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut fake_randomness_acc,
+                    &mut switchboard_acc,
+                    &mut clock_sysvar_acc,
+                ]
+                .into_iter()
+                .chain(vrf_request_accs.iter_mut())
+                .collect()
+            )
+        );
+
+        // BadCase: randomness is requested from another program
+        let mut instruction = crate::instruction::request_randomness(
+            &program_id,
+            &lottery_key,
+            &lottery_authority,
+            &randomness_account,
+            &vrf_request_accounts,
+            1,
+            2,
+        )
+        .unwrap();
+        instruction.accounts[3].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(ProgramError::IncorrectProgramId),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut switchboard_acc,
+                    &mut clock_sysvar_acc,
+                ]
+                .into_iter()
+                .chain(vrf_request_accs.iter_mut())
+                .collect()
+            )
+        );
+
+        do_process(
+            crate::instruction::request_randomness(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                &randomness_account,
+                &vrf_request_accounts,
+                1,
+                2,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut randomness_acc,
+                &mut switchboard_acc,
+                &mut clock_sysvar_acc,
+            ]
+            .into_iter()
+            .chain(vrf_request_accs.iter_mut())
+            .collect(),
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::AwaitingRandomness);
+        assert_eq!(lottery.randomness_request_slot, 43);
+        assert_eq!(lottery.request_vrf_counter, 0);
+
+        // BadCase: randomness is already requested
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::request_randomness(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                    &vrf_request_accounts,
+                    1,
+                    2,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut switchboard_acc,
+                    &mut clock_sysvar_acc,
+                ]
+                .into_iter()
+                .chain(vrf_request_accs.iter_mut())
+                .collect()
+            )
+        );
+
+        // BadCase: draw time is not reached
+        assert_eq!(
+            Err(LotteryError::DrawTooEarly.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 44,
+            unix_timestamp: DRAW_AFTER_TS,
+            ..Default::default()
+        });
+
+        // BadCase: draw with another randomness account
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessAccount.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &fake_randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut fake_randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_switchboard_request_randomness() {
+        let vrf_account = Pubkey::new_unique();
+        let vrf_authority = Pubkey::new_unique();
+        let payer_authority = Pubkey::new_unique();
+        let request_accounts = switchboard::VrfRequestAccounts {
+            oracle_queue: Pubkey::new_unique(),
+            queue_authority: Pubkey::new_unique(),
+            data_buffer: Pubkey::new_unique(),
+            permission: Pubkey::new_unique(),
+            escrow: Pubkey::new_unique(),
+            payer_wallet: Pubkey::new_unique(),
+            program_state: Pubkey::new_unique(),
+        };
+        let instruction = switchboard::request_randomness(
+            &vrf_account,
+            &vrf_authority,
+            &payer_authority,
+            &request_accounts,
+            254,
+            253,
+        );
+        assert_eq!(instruction.program_id, switchboard::id());
+        assert_eq!(
+            instruction.data,
+            [230, 121, 14, 164, 28, 222, 117, 118, 254, 253].to_vec()
+        );
+        assert_eq!(
+            instruction.accounts,
+            vec![
+                AccountMeta::new_readonly(vrf_authority, true),
+                AccountMeta::new(vrf_account, false),
+                AccountMeta::new(request_accounts.oracle_queue, false),
+                AccountMeta::new_readonly(request_accounts.queue_authority, false),
+                AccountMeta::new_readonly(request_accounts.data_buffer, false),
+                AccountMeta::new(request_accounts.permission, false),
+                AccountMeta::new(request_accounts.escrow, false),
+                AccountMeta::new(request_accounts.payer_wallet, false),
+                AccountMeta::new_readonly(payer_authority, true),
+                AccountMeta::new_readonly(recent_blockhashes::id(), false),
+                AccountMeta::new_readonly(request_accounts.program_state, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ]
+        );
+        assert_eq!(
+            hash(b"global:vrf_request_randomness").to_bytes()[..8],
+            switchboard::REQUEST_RANDOMNESS_DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn test_check_vrf_round() {
+        let lottery_data = LotteryData {
+            close_vrf_counter: 3,
+            request_vrf_counter: 5,
+            randomness_request_slot: 100,
+            ..Default::default()
+        };
+        let round = VrfRound {
+            counter: 6,
+            fulfillment_slot: 101,
+            randomness: vec![7; 32],
+        };
+        assert_eq!(Ok(()), check_vrf_round(&round, &lottery_data));
+
+        // BadCase: round is known at sales close
+        assert_eq!(
+            Err(LotteryError::StaleRandomness.into()),
+            check_vrf_round(
+                &VrfRound {
+                    counter: 3,
+                    fulfillment_slot: 101,
+                    randomness: vec![7; 32],
+                },
+                &LotteryData {
+                    request_vrf_counter: 0,
+                    ..lottery_data
+                }
+            )
+        );
+
+        // BadCase: round is known at randomness request
+        assert_eq!(
+            Err(LotteryError::StaleRandomness.into()),
+            check_vrf_round(
+                &VrfRound {
+                    counter: 5,
+                    fulfillment_slot: 101,
+                    randomness: vec![7; 32],
+                },
+                &lottery_data
+            )
+        );
+
+        // BadCase: round is fulfilled before randomness request slot
+        assert_eq!(
+            Err(LotteryError::StaleRandomness.into()),
+            check_vrf_round(
+                &VrfRound {
+                    counter: 6,
+                    fulfillment_slot: 99,
+                    randomness: vec![7; 32],
+                },
+                &lottery_data
+            )
+        );
+    }
+
+    #[test]
+    fn test_reveal_seed() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let randomness_account = Pubkey::new_unique();
+        let mut randomness_acc = SolanaAccount::default();
+        let game_matrix = test_game_matrix();
+        let seed = [7u8; 32];
+        let seed_commitment = hash(&seed).to_bytes();
+
+        // BadCase: commit-reveal lottery without seed commitment
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessMode.into()),
+            do_process(
+                crate::instruction::initialize_lottery(
+                    &program_id,
+                    lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        randomness_mode: RandomnessMode::CommitReveal,
+                        game_matrix,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut vault_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    randomness_mode: RandomnessMode::CommitReveal,
+                    seed_commitment,
+                    game_matrix,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.randomness_mode, RandomnessMode::CommitReveal);
+        assert_eq!(lottery.seed_commitment, seed_commitment);
+
+        // Slots are produced about 2.5 times a second, draw is allowed at slot 500
+        let slot_hash = |slot: u64| (slot, Hash::new_from_array([slot as u8; 32]));
+        let reveal_deadline_slot = 500 + SEED_REVEAL_WINDOW_SLOTS;
+        let mut slot_hashes_acc = create_account_for_test(&SlotHashes::new(
+            &(501..=reveal_deadline_slot)
+                .rev()
+                .map(slot_hash)
+                .collect::<Vec<_>>(),
+        ));
+
+        // BadCase: sales are not closed
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::reveal_seed(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &seed,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: commit-reveal sales close before draw time
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 250,
+            unix_timestamp: SALES_CLOSE_TS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::DrawTooEarly.into()),
+            do_process(
+                crate::instruction::close_sales(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    None
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 500,
+            unix_timestamp: DRAW_AFTER_TS,
+            ..Default::default()
+        });
+        do_process(
+            crate::instruction::close_sales(&program_id, &lottery_key, &lottery_authority, None)
+                .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: Switchboard randomness is requested for commit-reveal lottery
+        let mut switchboard_acc = SolanaAccount::default();
+        let vrf_request_accounts = switchboard::VrfRequestAccounts::default();
+        let mut vrf_request_accs = vec![SolanaAccount::default(); 9];
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessMode.into()),
+            do_process(
+                crate::instruction::request_randomness(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                    &vrf_request_accounts,
+                    1,
+                    2,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut switchboard_acc,
+                    &mut clock_sysvar_acc,
+                ]
+                .into_iter()
+                .chain(vrf_request_accs.iter_mut())
+                .collect()
+            )
+        );
+
+        // BadCase: Switchboard randomness is stored for commit-reveal lottery
+        assert_eq!(
+            Err(LotteryError::InvalidRandomnessMode.into()),
+            do_process(
+                crate::instruction::store_winning_numbers(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // Seed is revealed in the last slot the first slot after sales close is in SlotHashes,
+        // about 205 seconds after sales close
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: reveal_deadline_slot,
+            unix_timestamp: DRAW_AFTER_TS + 205,
+            ..Default::default()
+        });
+
+        // BadCase: seed does not match the commitment
+        assert_eq!(
+            Err(LotteryError::InvalidSeedReveal.into()),
+            do_process(
+                crate::instruction::reveal_seed(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &[8; 32],
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: account is not the SlotHashes sysvar
+        let mut instruction =
+            crate::instruction::reveal_seed(&program_id, &lottery_key, &lottery_authority, &seed)
+                .unwrap();
+        instruction.accounts[2].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            Err(ProgramError::InvalidArgument),
+            do_process(
+                instruction,
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: no slot after sales close yet
+        let mut early_slot_hashes_acc =
+            create_account_for_test(&SlotHashes::new(&[slot_hash(500), slot_hash(499)]));
+        assert_eq!(
+            Err(LotteryError::SlotHashNotAvailable.into()),
+            do_process(
+                crate::instruction::reveal_seed(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &seed,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut early_slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        // BadCase: sales close slot is not covered by SlotHashes anymore
+        let mut late_slot_hashes_acc = create_account_for_test(&SlotHashes::new(&[
+            slot_hash(reveal_deadline_slot + 1),
+            slot_hash(502),
+        ]));
+        assert_eq!(
+            Err(LotteryError::SlotHashNotAvailable.into()),
+            do_process(
+                crate::instruction::reveal_seed(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &seed,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut late_slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::reveal_seed(&program_id, &lottery_key, &lottery_authority, &seed)
+                .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut slot_hashes_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        // Seed is mixed with the hash of slot 501, the first slot after sales close
+        let random_numbers = hashv(&[&seed, slot_hash(501).1.as_ref()]).to_bytes();
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Drawn);
+        assert_eq!(
+            lottery.winning_numbers,
+            draw_numbers(&game_matrix, &random_numbers).unwrap()
+        );
+        assert_eq!(lottery.draw_seed, hash(&random_numbers).to_bytes());
+        assert_eq!(lottery.draw_proof.algorithm_version, DRAW_ALGORITHM_VERSION);
+        assert_eq!(
+            lottery.draw_proof.randomness_account,
+            solana_program::sysvar::slot_hashes::id()
+        );
+        assert_eq!(lottery.draw_proof.fulfillment_slot, 501);
+        assert_eq!(lottery.draw_proof.randomness(), &random_numbers[..]);
+        assert_eq!(lottery.draw_proof.randomness_mode, RandomnessMode::CommitReveal);
+        assert_eq!(lottery.draw_proof.seed, seed);
+        assert_eq!(lottery.draw_proof.slot_hash, slot_hash(501).1.to_bytes());
+
+        // BadCase: seed is already revealed
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::reveal_seed(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &seed,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut slot_hashes_acc,
+                    &mut clock_sysvar_acc,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_cancel_lottery() {
+        let program_id = id();
+        let lottery_id = 112234;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let user_wallet = Pubkey::new_unique();
+        let mut user_wallet_acc = SolanaAccount::default();
+        let ticket_price = sol_to_lamports(0.1);
+        let seed_commitment = hash(&[7u8; 32]).to_bytes();
+
+        let user_ticket = Pubkey::new_unique();
+        let mut user_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    randomness_mode: RandomnessMode::CommitReveal,
+                    seed_commitment,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // Quick pick ticket numbers are never assigned without the draw
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: user_wallet,
+                quick_pick: true,
+                lottery: lottery_key,
+                lottery_id,
+                price_paid: ticket_price,
+                ..Default::default()
+            },
+            &mut user_ticket_acc.data,
+        )
+        .unwrap();
+
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::SalesClosed;
+        lottery.sales_close_slot = 42;
+        lottery.total_registrations = 1;
+        lottery.prize_pool_amount = ticket_price;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+
+        // BadCase: refund of not cancelled lottery
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::refund_ticket(
+                    &program_id,
+                    &lottery_key,
+                    &user_ticket,
+                    &user_wallet,
+                    false,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut vault_acc,
+                    &mut user_wallet_acc,
+                    &mut system_acc,
+                ]
+            )
+        );
+
+        // BadCase: seed can still be revealed
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 42 + SEED_REVEAL_WINDOW_SLOTS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::CancelTooEarly.into()),
+            do_process(
+                crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+                vec![&mut lottery_acc, &mut clock_sysvar_acc]
+            )
+        );
+
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 43 + SEED_REVEAL_WINDOW_SLOTS,
+            ..Default::default()
+        });
+        do_process(
+            crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+            vec![&mut lottery_acc, &mut clock_sysvar_acc],
+        )
+        .unwrap();
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Cancelled);
+
+        // BadCase: lottery is already cancelled
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+                vec![&mut lottery_acc, &mut clock_sysvar_acc]
+            )
+        );
+
+        // BadCase: refund to another wallet
+        let other_wallet = Pubkey::new_unique();
+        assert_eq!(
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
+            do_process(
+                crate::instruction::refund_ticket(
+                    &program_id,
+                    &lottery_key,
+                    &user_ticket,
+                    &other_wallet,
+                    false,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut vault_acc,
+                    &mut user_wallet_acc,
+                    &mut system_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::refund_ticket(
+                &program_id,
+                &lottery_key,
+                &user_ticket,
+                &user_wallet,
+                false,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user_ticket_acc,
+                &mut vault_acc,
+                &mut user_wallet_acc,
+                &mut system_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.prize_pool_amount, 0);
+        let ticket = TicketData::unpack_unchecked(&user_ticket_acc.data).unwrap();
+        assert!(ticket.is_claimed);
+
+        // BadCase: ticket is already refunded
+        assert_eq!(
+            Err(LotteryError::AlreadyClaimed.into()),
+            do_process(
+                crate::instruction::refund_ticket(
+                    &program_id,
+                    &lottery_key,
+                    &user_ticket,
+                    &user_wallet,
+                    false,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user_ticket_acc,
+                    &mut vault_acc,
+                    &mut user_wallet_acc,
+                    &mut system_acc,
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_cancel_switchboard_lottery() {
+        let program_id = id();
+        let lottery_id = 112235;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let user_wallet = Pubkey::new_unique();
+        let mut user_wallet_acc = SolanaAccount::default();
+        let ticket_price = sol_to_lamports(0.1);
+
+        let user_ticket = Pubkey::new_unique();
+        let mut user_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+        vault_acc.lamports += ticket_price;
+
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: user_wallet,
+                ticket_number_arr: [1, 2, 3, 4, 5, 6],
+                lottery: lottery_key,
+                lottery_id,
+                price_paid: ticket_price,
+                ..Default::default()
+            },
+            &mut user_ticket_acc.data,
+        )
+        .unwrap();
+
+        // Vrf request is never fulfilled
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::AwaitingRandomness;
+        lottery.sales_close_slot = 250;
+        lottery.randomness_request_slot = 500;
+        lottery.total_registrations = 1;
+        lottery.prize_pool_amount = ticket_price;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+
+        // BadCase: draw is not overdue
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 500 + VRF_FULFILLMENT_TIMEOUT_SLOTS + 1,
+            unix_timestamp: DRAW_AFTER_TS + DRAW_TIMEOUT_SECS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::CancelTooEarly.into()),
+            do_process(
+                crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+                vec![&mut lottery_acc, &mut clock_sysvar_acc]
+            )
+        );
+
+        // BadCase: requested Vrf round can still be fulfilled
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 500 + VRF_FULFILLMENT_TIMEOUT_SLOTS,
+            unix_timestamp: DRAW_AFTER_TS + DRAW_TIMEOUT_SECS + 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::CancelTooEarly.into()),
+            do_process(
+                crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+                vec![&mut lottery_acc, &mut clock_sysvar_acc]
+            )
+        );
+
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            slot: 500 + VRF_FULFILLMENT_TIMEOUT_SLOTS + 1,
+            unix_timestamp: DRAW_AFTER_TS + DRAW_TIMEOUT_SECS + 1,
+            ..Default::default()
+        });
+        do_process(
+            crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+            vec![&mut lottery_acc, &mut clock_sysvar_acc],
+        )
+        .unwrap();
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Cancelled);
+
+        do_process(
+            crate::instruction::refund_ticket(
+                &program_id,
+                &lottery_key,
+                &user_ticket,
+                &user_wallet,
+                false,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user_ticket_acc,
+                &mut vault_acc,
+                &mut user_wallet_acc,
+                &mut system_acc,
+            ],
+        )
+        .unwrap();
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.prize_pool_amount, 0);
+        assert!(TicketData::unpack_unchecked(&user_ticket_acc.data).unwrap().is_claimed);
+
+        // BadCase: drawn lottery can not be cancelled
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::Drawn;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::cancel_lottery(&program_id, &lottery_key).unwrap(),
+                vec![&mut lottery_acc, &mut clock_sysvar_acc]
+            )
+        );
     }
 
+    #[cfg(feature = "test-randomness")]
     #[test]
-    fn test_init_lottery() {
+    fn test_store_deterministic_winning_numbers() {
         let program_id = id();
         let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
         let mut lottery_acc = SolanaAccount::new(
             lottery_minimum_balance(),
             LotteryData::get_packed_len(),
             &program_id,
         );
         let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
-        let holding_wallet = Pubkey::new_unique();
-        let rewards_wallet = Pubkey::new_unique();
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
         let randomness_account = Pubkey::new_unique();
+        let mut randomness_acc = SolanaAccount::default();
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+        let game_matrix = test_game_matrix();
+        let randomness = [3u8; 32];
 
-        // BadCase: rent NotRentExempt
-        let mut bad_lottery_acc = SolanaAccount::new(
-            lottery_minimum_balance() - 100,
-            LotteryData::get_packed_len(),
-            &program_id,
-        );
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    randomness_account,
+                    randomness_mode: RandomnessMode::Deterministic,
+                    seed_commitment: randomness,
+                    game_matrix,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: sales are not closed
         assert_eq!(
-            Err(LotteryError::NotRentExempt.into()),
+            Err(LotteryError::InvalidStatusTransition.into()),
             do_process(
-                crate::instruction::initialize_lottery(
+                crate::instruction::store_winning_numbers(
                     &program_id,
-                    lottery_id,
-                    
-                    &holding_wallet,
-                    &rewards_wallet,
-                    
+                    &lottery_key,
+                    &lottery_authority,
                     &randomness_account,
-                    &lottery_key
                 )
                 .unwrap(),
-                vec![&mut bad_lottery_acc, &mut rent_sysvar_acc]
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
             )
         );
 
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_CLOSE_TS,
+            ..Default::default()
+        });
         do_process(
-            crate::instruction::initialize_lottery(
-                &program_id,
-                lottery_id,
-               
-                &holding_wallet,
-                &rewards_wallet,
-               
-                &randomness_account,
-                &lottery_key,
-            )
-            .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
+            crate::instruction::close_sales(&program_id, &lottery_key, &lottery_authority, None)
+                .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut clock_sysvar_acc,
+            ],
         )
         .unwrap();
 
-        // BadCase: Lottery Already initialized
+        // BadCase: draw time is not reached
         assert_eq!(
-            Err(LotteryError::Initialized.into()),
+            Err(LotteryError::DrawTooEarly.into()),
             do_process(
-                crate::instruction::initialize_lottery(
+                crate::instruction::store_winning_numbers(
                     &program_id,
-                    lottery_id,
-                    
-                    &holding_wallet,
-                    &rewards_wallet,
-                   
-                    &randomness_account,
                     &lottery_key,
+                    &lottery_authority,
+                    &randomness_account,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc, &mut rent_sysvar_acc]
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut randomness_acc,
+                    &mut clock_sysvar_acc,
+                ]
             )
         );
 
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            unix_timestamp: DRAW_AFTER_TS,
+            ..Default::default()
+        });
+        do_process(
+            crate::instruction::store_winning_numbers(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                &randomness_account,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut randomness_acc,
+                &mut clock_sysvar_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
         let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
-        assert_eq!(lottery.is_initialized, true);
-        assert_eq!(lottery.lottery_id, lottery_id);
-        
-        assert_eq!(lottery.holding_wallet, holding_wallet);
-        assert_eq!(lottery.rewards_wallet, rewards_wallet);
-        
-       
-        assert_eq!(lottery.total_registrations, 0);
-        assert_eq!(lottery.prize_pool_amount, 0);
-        for number in &lottery.winning_numbers {
-            assert_eq!(*number, 0);
-        }
+        assert_eq!(lottery.status, LotteryStatus::Drawn);
+        assert_eq!(
+            lottery.winning_numbers,
+            draw_numbers(&game_matrix, &randomness).unwrap()
+        );
+        assert_eq!(lottery.draw_seed, hash(&randomness).to_bytes());
+        assert_eq!(lottery.draw_proof.randomness(), &randomness[..]);
     }
 
     #[test]
-    fn test_ticket_purchase() {
+    fn test_assign_quick_picks() {
         let program_id = id();
         let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
         let mut lottery_acc = SolanaAccount::new(
             lottery_minimum_balance(),
             LotteryData::get_packed_len(),
             &program_id,
         );
-        let user_funding_key = Pubkey::new_unique();
-        let mut user_funding_acc = SolanaAccount::default();
-        let user_ticket_key = Pubkey::new_unique();
-        let mut user_ticket_acc = SolanaAccount::new(
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let game_matrix = test_game_matrix();
+        let ticket_key = Pubkey::new_unique();
+        let mut ticket_acc = SolanaAccount::new(
             ticket_minimum_balance(),
             TicketData::get_packed_len(),
             &program_id,
         );
-        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: Pubkey::new_unique(),
+                quick_pick: true,
+                lottery: lottery_key,
+                lottery_id: lottery_id - 1,
+                ..Default::default()
+            },
+            &mut ticket_acc.data,
+        )
+        .unwrap();
+
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    game_matrix,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: lottery is not drawn
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::assign_quick_picks(&program_id, &lottery_key, &[ticket_key])
+                    .unwrap(),
+                vec![&mut lottery_acc, &mut ticket_acc]
+            )
+        );
+
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::Drawn;
+        lottery.winning_numbers = [10, 20, 30, 40, 50, 15];
+        lottery.draw_seed = [7; 32];
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+
+        // BadCase: ticket is purchased in the previous lottery round
+        assert_eq!(
+            Err(LotteryError::InvalidTicketLottery.into()),
+            do_process(
+                crate::instruction::assign_quick_picks(&program_id, &lottery_key, &[ticket_key])
+                    .unwrap(),
+                vec![&mut lottery_acc, &mut ticket_acc]
+            )
+        );
+
+        let mut ticket = TicketData::unpack_unchecked(&ticket_acc.data).unwrap();
+        ticket.lottery_id = lottery_id;
+        TicketData::pack(ticket, &mut ticket_acc.data).unwrap();
+
+        do_process(
+            crate::instruction::assign_quick_picks(&program_id, &lottery_key, &[ticket_key])
+                .unwrap(),
+            vec![&mut lottery_acc, &mut ticket_acc],
+        )
+        .unwrap();
+
+        let ticket = TicketData::unpack_unchecked(&ticket_acc.data).unwrap();
+        assert!(!ticket.is_quick_pick_pending());
+        assert!(game_matrix.is_valid_combination(&ticket.ticket_number_arr));
+        assert_eq!(
+            Some(ticket.ticket_number_arr),
+            quick_pick_numbers(&game_matrix, &[7; 32], &ticket_key)
+        );
+
+        // BadCase: numbers are already assigned
+        assert_eq!(
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
+            do_process(
+                crate::instruction::assign_quick_picks(&program_id, &lottery_key, &[ticket_key])
+                    .unwrap(),
+                vec![&mut lottery_acc, &mut ticket_acc]
+            )
+        );
+    }
+
+    #[test]
+    fn test_reward_winners() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let lottery_result_key = Pubkey::new_unique();
+        let mut lottery_result_acc = SolanaAccount::new(
+            lottery_result_minimum_balance(),
+            LotteryResultData::get_packed_len(),
+            &program_id,
+        );
         let mut system_acc = SolanaAccount::default();
         let mut spl_token_acc = SolanaAccount::default();
-        
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut clock_sysvar_acc = create_account_for_test(&Clock::default());
+       
         let holding_wallet = Pubkey::new_unique();
         let mut holding_wallet_acc = SolanaAccount::default();
         let rewards_wallet = Pubkey::new_unique();
+        let mut rewards_wallet_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
        
+
         let randomness_account = Pubkey::new_unique();
-        let user_charity = charity_1;
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
 
-        let user_lifetime_ticket_key = Pubkey::new_unique();
-        let mut user_lifetime_ticket_acc =
+        let user1_wallet = Pubkey::new_unique();
+        let mut user1_wallet_acc = SolanaAccount::default();
+        let user1_ticket = Pubkey::new_unique();
+        let mut user1_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+        let user2_wallet = Pubkey::new_unique();
+        let mut user2_wallet_acc = SolanaAccount::default();
+        let user2_ticket = Pubkey::new_unique();
+        let mut user2_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
+        );
+
+        let user1_lifetime_ticket_key = Pubkey::new_unique();
+        let mut user1_lifetime_ticket_acc =
+            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
+        let user2_lifetime_ticket_key = Pubkey::new_unique();
+        let mut user2_lifetime_ticket_acc =
             SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
         let lifetime_ticket_mint_key = Pubkey::new_unique();
         let mut lifetime_ticket_mint_acc =
@@ -806,30 +5810,30 @@ mod test {
         assert_eq!(
             Err(LotteryError::NotInitialized.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 15],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
+                   
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc
                 ]
             )
         );
@@ -838,413 +5842,531 @@ mod test {
             crate::instruction::initialize_lottery(
                 &program_id,
                 lottery_id,
-               
-                &holding_wallet,
-                &rewards_wallet,
-                
-                &randomness_account,
-                &lottery_key,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
             )
             .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
         )
         .unwrap();
 
-        // BadCase: user cannot pay
+        // BadCase: Lottery is not drawn
         assert_eq!(
-            Err(ProgramError::InsufficientFunds),
+            Err(LotteryError::InvalidStatusTransition.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 29],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
+                    
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc
                 ]
             )
         );
 
-        user_funding_acc.lamports += sol_to_lamports(0.1);
-
-        // BadCase: rent NotRentExempt
-        let mut bad_ticket_acc = SolanaAccount::new(
-            ticket_minimum_balance() - 100,
-            TicketData::get_packed_len(),
-            &program_id,
-        );
+        // BadCase: user cannot pay for ticket
+        let user1_charity = Pubkey::new_unique();
         assert_eq!(
-            Err(LotteryError::NotRentExempt.into()),
+            Err(ProgramError::InsufficientFunds),
             do_process(
                 crate::instruction::purchase_ticket(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 15],
-                    &user_ticket_key,
-                    &holding_wallet,
+                    &user1_charity,
+                    &user1_wallet,
+                    &[1, 2, 3, 4, 55, 6],
+                    ticket_price,
+                    false,
+                    &user1_ticket,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
+                    &user1_lifetime_ticket_key,
                     &lifetime_ticket_owner_key,
                     &lifetime_ticket_mint_key,
+                    None,
+                    None,
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut bad_ticket_acc,
-                    &mut user_funding_acc,
-                    &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut vault_acc,
+                    &mut user1_lifetime_ticket_acc,
                     &mut lifetime_ticket_owner_acc,
                     &mut lifetime_ticket_mint_acc,
                     &mut rent_sysvar_acc,
                     &mut system_acc,
                     &mut spl_token_acc,
-                ]
+                    &mut clock_sysvar_acc,
+                ],
             )
         );
 
-        // BadCase: bad numbers
+        // Purchase tickets
+        user1_wallet_acc.lamports += sol_to_lamports(0.1);
+        do_process(
+            crate::instruction::purchase_ticket(
+                &program_id,
+                &user1_charity,
+                &user1_wallet,
+                &[11, 22, 33, 44, 51, 1],
+                ticket_price,
+                false,
+                &user1_ticket,
+                &lottery_key,
+                &user1_lifetime_ticket_key,
+                &lifetime_ticket_owner_key,
+                &lifetime_ticket_mint_key,
+                None,
+                None,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user1_ticket_acc,
+                &mut user1_wallet_acc,
+                &mut vault_acc,
+                &mut user1_lifetime_ticket_acc,
+                &mut lifetime_ticket_owner_acc,
+                &mut lifetime_ticket_mint_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut spl_token_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        let user2_charity = Pubkey::new_unique();
+        user2_wallet_acc.lamports += sol_to_lamports(0.1);
+        do_process(
+            crate::instruction::purchase_ticket(
+                &program_id,
+                &user2_charity,
+                &user2_wallet,
+                &[5, 4, 3, 2, 66, 8],
+                ticket_price,
+                false,
+                &user2_ticket,
+                &lottery_key,
+                &user2_lifetime_ticket_key,
+                &lifetime_ticket_owner_key,
+                &lifetime_ticket_mint_key,
+                None,
+                None,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut user2_ticket_acc,
+                &mut user2_wallet_acc,
+                &mut vault_acc,
+                &mut user2_lifetime_ticket_acc,
+                &mut lifetime_ticket_owner_acc,
+                &mut lifetime_ticket_mint_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut spl_token_acc,
+                &mut clock_sysvar_acc,
+            ],
+        )
+        .unwrap();
+
+        // Store winning numbers
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::Drawn;
+        lottery.closed_registrations = lottery.total_registrations;
+        lottery.closed_prize_pool = lottery.prize_pool_amount;
+        lottery.winning_numbers = [2, 3, 4, 5, 66, 7];
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+
+        // BadCase: not enough users accounts
         assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[70, 20, 30, 40, 50, 15],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
+                    
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc
                 ]
             )
         );
 
+        // BadCase: Bad wallet user pk in ticket data
+        let user1_fake_wallet = Pubkey::new_unique();
+        let mut user1_fake_wallet_acc = SolanaAccount::default();
         assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 0, 15],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![
+                        (user1_ticket, user1_fake_wallet),
+                        (user2_ticket, user2_wallet)
+                    ],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
+                   
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_fake_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc
                 ]
             )
         );
 
+        // BadCase: Bad sollotto reward account
+        let fake_sollotto_labs_wallet = Pubkey::new_unique();
         assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
+            Err(LotteryError::InvalidSollottoAccount.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 30],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
+                   
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc
                 ]
             )
         );
 
-        do_process(
-            crate::instruction::purchase_ticket(
-                &program_id,
-                &user_charity,
-                &user_funding_key,
-                &[10, 20, 30, 40, 50, 29],
-                &user_ticket_key,
-                &holding_wallet,
-                &lottery_key,
-                &user_lifetime_ticket_key,
-                &lifetime_ticket_owner_key,
-                &lifetime_ticket_mint_key,
-            )
-            .unwrap(),
-            vec![
-                &mut lottery_acc,
-                &mut user_ticket_acc,
-                &mut user_funding_acc,
-                &mut holding_wallet_acc,
-                &mut user_lifetime_ticket_acc,
-                &mut lifetime_ticket_owner_acc,
-                &mut lifetime_ticket_mint_acc,
-                &mut rent_sysvar_acc,
-                &mut system_acc,
-                &mut spl_token_acc,
-            ],
-        )
-        .unwrap();
-
-        let lottery = LotteryData::unpack(&lottery_acc.data()).unwrap();
-        assert_eq!(lottery.charity_1_vc, 1);
-        assert_eq!(lottery.total_registrations, 1);
-        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.1));
+        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
+        assert_eq!(lottery.total_registrations, 2);
+        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.2));
+        
+        assert_eq!(lottery.winning_numbers, [2, 3, 4, 5, 66, 7]);
 
-        // BadCase: Ticket already purchased
+        // BadCase: ticket is purchased in another lottery
+        vault_acc.lamports += sol_to_lamports(10.0);
+        let mut ticket = TicketData::unpack_unchecked(&user2_ticket_acc.data).unwrap();
+        ticket.lottery = Pubkey::new_unique();
+        TicketData::pack(ticket, &mut user2_ticket_acc.data).unwrap();
         assert_eq!(
-            Err(LotteryError::AlreadyPurchased.into()),
+            Err(LotteryError::InvalidTicketLottery.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 30],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc,
                 ]
             )
         );
+        ticket.lottery = lottery_key;
+        TicketData::pack(ticket, &mut user2_ticket_acc.data).unwrap();
 
+        // User2 wins tier 5 with numbers in another order
         do_process(
-            crate::instruction::store_winning_numbers(
+            crate::instruction::reward_winners(
                 &program_id,
-                &[10, 20, 30, 40, 50, 29],
                 &lottery_key,
+                &lottery_authority,
+                &lottery_result_key,
+                &holding_wallet,
+                &rewards_wallet,
+                false,
+                &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
             )
             .unwrap(),
-            vec![&mut lottery_acc],
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut lottery_result_acc,
+                &mut vault_acc,
+                &mut holding_wallet_acc,
+                &mut rewards_wallet_acc,
+               
+                &mut system_acc,
+                &mut user1_ticket_acc,
+                &mut user1_wallet_acc,
+                &mut user2_ticket_acc,
+                &mut user2_wallet_acc,
+            ],
         )
         .unwrap();
 
-        let user_funding_key = Pubkey::new_unique();
-        let mut user_funding_acc = SolanaAccount::default();
-        let user_ticket_key = Pubkey::new_unique();
-        let mut user_ticket_acc = SolanaAccount::new(
-            ticket_minimum_balance(),
-            TicketData::get_packed_len(),
-            &program_id,
-        );
+        // Check data
+        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
+        assert_eq!(lottery.is_initialized, true);
+        assert_eq!(lottery.status, LotteryStatus::Settled);
+        assert_eq!(lottery.lottery_id, lottery_id);
+        assert_eq!(lottery.total_registrations, 2);
+        assert_eq!(lottery.winning_numbers, [2, 3, 4, 5, 66, 7]);
+        assert_eq!(lottery.tier_payouts[2], sol_to_lamports(0.01));
+        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.01));
+
+        let lottery_result =
+            LotteryResultData::unpack_unchecked(lottery_result_acc.data()).unwrap();
+        assert_eq!(lottery_result.lottery_id, lottery_id);
+        assert_eq!(lottery_result.winning_numbers, [2, 3, 4, 5, 66, 7]);
 
+        // BadCase: Lottery is already settled
         assert_eq!(
-            Err(LotteryError::IsFinaled.into()),
+            Err(LotteryError::InvalidStatusTransition.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::reward_winners(
                     &program_id,
-                    &user_charity,
-                    &user_funding_key,
-                    &[10, 20, 30, 40, 50, 30],
-                    &user_ticket_key,
-                    &holding_wallet,
                     &lottery_key,
-                    &user_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &lottery_result_key,
+                    &holding_wallet,
+                    &rewards_wallet,
+                    false,
+                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user_ticket_acc,
-                    &mut user_funding_acc,
+                    &mut lottery_authority_acc,
+                    &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
-                    &mut user_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
+                    &mut rewards_wallet_acc,
                     &mut system_acc,
-                    &mut spl_token_acc,
+                    &mut user1_ticket_acc,
+                    &mut user1_wallet_acc,
+                    &mut user2_ticket_acc,
+                    &mut user2_wallet_acc,
                 ]
             )
         );
-    }
-
-    #[test]
-    fn test_store_winning_numbers() {
-        let program_id = id();
-        let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
-        let mut lottery_acc = SolanaAccount::new(
-            lottery_minimum_balance(),
-            LotteryData::get_packed_len(),
-            &program_id,
-        );
-        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
-       
-        let holding_wallet = Pubkey::new_unique();
-        let rewards_wallet = Pubkey::new_unique();
-       
-        let randomness_account = Pubkey::new_unique();
 
-        // BadCase: Lottery is not initialized
+        // BadCase: ticket has no prize
         assert_eq!(
-            Err(LotteryError::NotInitialized.into()),
+            Err(LotteryError::NoPrize.into()),
             do_process(
-                crate::instruction::store_winning_numbers(
+                crate::instruction::claim_prize(
                     &program_id,
-                    &[10, 20, 30, 40, 50, 29],
                     &lottery_key,
+                    &user1_ticket,
+                    &user1_wallet,
+                    false,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc]
+                vec![
+                    &mut lottery_acc,
+                    &mut user1_ticket_acc,
+                    &mut vault_acc,
+                    &mut user1_wallet_acc,
+                    &mut system_acc,
+                ]
             )
         );
 
-        do_process(
-            crate::instruction::initialize_lottery(
-                &program_id,
-                lottery_id,
-               
-                &holding_wallet,
-                &rewards_wallet,
-               
-                &randomness_account,
-                &lottery_key,
-            )
-            .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
-        )
-        .unwrap();
-
-        // BadCase: Bad numbers
+        // BadCase: prize is claimed to another wallet
         assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
             do_process(
-                crate::instruction::store_winning_numbers(
+                crate::instruction::claim_prize(
                     &program_id,
-                    &[70, 20, 30, 40, 50, 29],
                     &lottery_key,
+                    &user2_ticket,
+                    &user1_wallet,
+                    false,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc]
+                vec![
+                    &mut lottery_acc,
+                    &mut user2_ticket_acc,
+                    &mut vault_acc,
+                    &mut user1_wallet_acc,
+                    &mut system_acc,
+                ]
             )
         );
 
-        assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
-            do_process(
-                crate::instruction::store_winning_numbers(
-                    &program_id,
-                    &[10, 20, 30, 40, 0, 29],
-                    &lottery_key,
-                )
-                .unwrap(),
-                vec![&mut lottery_acc]
-            )
+        // BadCase: winning ticket is purchased in another lottery
+        let foreign_ticket = Pubkey::new_unique();
+        let mut foreign_ticket_acc = SolanaAccount::new(
+            ticket_minimum_balance(),
+            TicketData::get_packed_len(),
+            &program_id,
         );
-
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: user2_wallet,
+                ticket_number_arr: [2, 3, 4, 5, 66, 7],
+                lottery: Pubkey::new_unique(),
+                lottery_id,
+                ..Default::default()
+            },
+            &mut foreign_ticket_acc.data,
+        )
+        .unwrap();
         assert_eq!(
-            Err(LotteryError::InvalidNumber.into()),
+            Err(LotteryError::InvalidTicketLottery.into()),
             do_process(
-                crate::instruction::store_winning_numbers(
+                crate::instruction::claim_prize(
                     &program_id,
-                    &[10, 20, 30, 40, 50, 30],
                     &lottery_key,
+                    &foreign_ticket,
+                    &user2_wallet,
+                    false,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc]
+                vec![
+                    &mut lottery_acc,
+                    &mut foreign_ticket_acc,
+                    &mut vault_acc,
+                    &mut user2_wallet_acc,
+                    &mut system_acc,
+                ]
             )
         );
 
         do_process(
-            crate::instruction::store_winning_numbers(
+            crate::instruction::claim_prize(
                 &program_id,
-                &[10, 20, 30, 40, 50, 29],
                 &lottery_key,
+                &user2_ticket,
+                &user2_wallet,
+                false,
             )
             .unwrap(),
-            vec![&mut lottery_acc],
+            vec![
+                &mut lottery_acc,
+                &mut user2_ticket_acc,
+                &mut vault_acc,
+                &mut user2_wallet_acc,
+                &mut system_acc,
+            ],
         )
         .unwrap();
 
-        let lottery = LotteryData::unpack(&lottery_acc.data()).unwrap();
-        assert_eq!(lottery.winning_numbers[0], 10);
-        assert_eq!(lottery.winning_numbers[1], 20);
-        assert_eq!(lottery.winning_numbers[2], 30);
-        assert_eq!(lottery.winning_numbers[3], 40);
-        assert_eq!(lottery.winning_numbers[4], 50);
-        assert_eq!(lottery.winning_numbers[5], 29);
+        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
+        assert_eq!(lottery.prize_pool_amount, 0);
+        let ticket = TicketData::unpack_unchecked(user2_ticket_acc.data()).unwrap();
+        assert!(ticket.is_claimed);
+
+        // BadCase: prize is already claimed
+        assert_eq!(
+            Err(LotteryError::AlreadyClaimed.into()),
+            do_process(
+                crate::instruction::claim_prize(
+                    &program_id,
+                    &lottery_key,
+                    &user2_ticket,
+                    &user2_wallet,
+                    false,
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut user2_ticket_acc,
+                    &mut vault_acc,
+                    &mut user2_wallet_acc,
+                    &mut system_acc,
+                ]
+            )
+        );
     }
 
     #[test]
-    fn test_reward_winners() {
+    fn test_settle_batch() {
         let program_id = id();
         let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
         let mut lottery_acc = SolanaAccount::new(
             lottery_minimum_balance(),
             LotteryData::get_packed_len(),
@@ -1257,27 +6379,22 @@ mod test {
             &program_id,
         );
         let mut system_acc = SolanaAccount::default();
-        let mut spl_token_acc = SolanaAccount::default();
         let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
-       
         let holding_wallet = Pubkey::new_unique();
         let mut holding_wallet_acc = SolanaAccount::default();
         let rewards_wallet = Pubkey::new_unique();
         let mut rewards_wallet_acc = SolanaAccount::default();
-       
-
+        let mut vault_acc = SolanaAccount::default();
         let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
 
-        let user1_wallet = Pubkey::new_unique();
-        let mut user1_wallet_acc = SolanaAccount::default();
         let user1_ticket = Pubkey::new_unique();
         let mut user1_ticket_acc = SolanaAccount::new(
             ticket_minimum_balance(),
             TicketData::get_packed_len(),
             &program_id,
         );
-        let user2_wallet = Pubkey::new_unique();
-        let mut user2_wallet_acc = SolanaAccount::default();
         let user2_ticket = Pubkey::new_unique();
         let mut user2_ticket_acc = SolanaAccount::new(
             ticket_minimum_balance(),
@@ -1285,466 +6402,1140 @@ mod test {
             &program_id,
         );
 
-        let user1_lifetime_ticket_key = Pubkey::new_unique();
-        let mut user1_lifetime_ticket_acc =
-            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
-        let user2_lifetime_ticket_key = Pubkey::new_unique();
-        let mut user2_lifetime_ticket_acc =
-            SolanaAccount::new(account_minimum_balance(), Account::LEN, &spl_token::id());
-        let lifetime_ticket_mint_key = Pubkey::new_unique();
-        let mut lifetime_ticket_mint_acc =
-            SolanaAccount::new(mint_minimum_balance(), Mint::LEN, &spl_token::id());
-        let lifetime_ticket_owner_key = Pubkey::new_unique();
-        let mut lifetime_ticket_owner_acc = SolanaAccount::default();
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
 
-        Mint::pack(
-            Mint {
-                is_initialized: true,
+        // User1 ticket has no prize, user2 ticket wins jackpot
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: Pubkey::new_unique(),
+                ticket_number_arr: [11, 22, 33, 44, 51, 1],
+                ticket_index: 0,
+                lottery: lottery_key,
+                lottery_id,
                 ..Default::default()
             },
-            &mut lifetime_ticket_mint_acc.data,
+            &mut user1_ticket_acc.data,
+        )
+        .unwrap();
+        TicketData::pack(
+            TicketData {
+                is_purchased: true,
+                user_wallet_pk: Pubkey::new_unique(),
+                ticket_number_arr: [2, 3, 4, 5, 66, 7],
+                ticket_index: 1,
+                lottery: lottery_key,
+                lottery_id,
+                ..Default::default()
+            },
+            &mut user2_ticket_acc.data,
+        )
+        .unwrap();
+
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::Drawn;
+        lottery.total_registrations = 2;
+        lottery.prize_pool_amount = sol_to_lamports(0.2);
+        lottery.closed_registrations = 2;
+        lottery.closed_prize_pool = sol_to_lamports(0.2);
+        lottery.winning_numbers = [2, 3, 4, 5, 66, 7];
+        lottery.draw_proof = DrawProof::new(
+            DRAW_ALGORITHM_VERSION,
+            RandomnessMode::SwitchboardVrf,
+            randomness_account,
+            77,
+            &[5; 32],
+        )
+        .unwrap();
+        lottery.ticket_ledger_hash = [9; 32];
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
+        vault_acc.lamports += sol_to_lamports(10.0);
+
+        // BadCase: tickets are not in purchase order
+        assert_eq!(
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
+            do_process(
+                crate::instruction::settle_batch(
+                    &program_id,
+                    &lottery_key,
+                    &lottery_authority,
+                    &[user2_ticket],
+                )
+                .unwrap(),
+                vec![
+                    &mut lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut user2_ticket_acc,
+                ]
+            )
+        );
+
+        do_process(
+            crate::instruction::settle_batch(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                &[user1_ticket],
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut user1_ticket_acc,
+            ],
         )
         .unwrap();
 
-        // BadCase: Lottery is not initialized
+        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Settling);
+        assert_eq!(lottery.settle_cursor, 1);
+        assert_eq!(lottery.tier_winners, [0; MAX_PRIZE_TIERS]);
+
+        // BadCase: ticket is settled twice
         assert_eq!(
-            Err(LotteryError::NotInitialized.into()),
+            Err(LotteryError::InvalidParticipantsAccounts.into()),
             do_process(
-                crate::instruction::reward_winners(
+                crate::instruction::settle_batch(
                     &program_id,
                     &lottery_key,
-                    &lottery_result_key,
-                    &holding_wallet,
-                    &rewards_wallet,
-                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
+                    &lottery_authority,
+                    &[user1_ticket],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut lottery_result_acc,
-                    &mut holding_wallet_acc,
-                    &mut rewards_wallet_acc,
-                   
-                    &mut system_acc,
+                    &mut lottery_authority_acc,
                     &mut user1_ticket_acc,
-                    &mut user1_wallet_acc,
-                    &mut user2_ticket_acc,
-                    &mut user2_wallet_acc
                 ]
             )
         );
 
-        do_process(
-            crate::instruction::initialize_lottery(
-                &program_id,
-                lottery_id,
-               
-                &holding_wallet,
-                &rewards_wallet,
-                
-                &randomness_account,
-                &lottery_key,
-            )
-            .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
-        )
-        .unwrap();
-
-        // BadCase: Lottery is not finaled
+        // BadCase: not all tickets are settled
         assert_eq!(
-            Err(LotteryError::NotFinaled.into()),
+            Err(LotteryError::SettlementIncomplete.into()),
             do_process(
-                crate::instruction::reward_winners(
+                crate::instruction::finalize_settlement(
                     &program_id,
                     &lottery_key,
+                    &lottery_authority,
                     &lottery_result_key,
                     &holding_wallet,
                     &rewards_wallet,
-                    
-                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
+                    false,
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
+                    &mut lottery_authority_acc,
                     &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
                     &mut rewards_wallet_acc,
-                    
                     &mut system_acc,
-                    &mut user1_ticket_acc,
-                    &mut user1_wallet_acc,
-                    &mut user2_ticket_acc,
-                    &mut user2_wallet_acc
                 ]
             )
         );
 
-        // BadCase: user cannot pay for ticket
-        let user1_charity = charity_1;
+        // BadCase: ticket is purchased in another lottery
+        let mut ticket = TicketData::unpack_unchecked(&user2_ticket_acc.data).unwrap();
+        ticket.lottery = Pubkey::new_unique();
+        TicketData::pack(ticket, &mut user2_ticket_acc.data).unwrap();
         assert_eq!(
-            Err(ProgramError::InsufficientFunds),
+            Err(LotteryError::InvalidTicketLottery.into()),
             do_process(
-                crate::instruction::purchase_ticket(
+                crate::instruction::settle_batch(
                     &program_id,
-                    &user1_charity,
-                    &user1_wallet,
-                    &[1, 2, 3, 4, 55, 6],
-                    &user1_ticket,
-                    &holding_wallet,
                     &lottery_key,
-                    &user1_lifetime_ticket_key,
-                    &lifetime_ticket_owner_key,
-                    &lifetime_ticket_mint_key,
+                    &lottery_authority,
+                    &[user2_ticket],
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
-                    &mut user1_ticket_acc,
-                    &mut user1_wallet_acc,
-                    &mut holding_wallet_acc,
-                    &mut user1_lifetime_ticket_acc,
-                    &mut lifetime_ticket_owner_acc,
-                    &mut lifetime_ticket_mint_acc,
-                    &mut rent_sysvar_acc,
-                    &mut system_acc,
-                    &mut spl_token_acc,
-                ],
+                    &mut lottery_authority_acc,
+                    &mut user2_ticket_acc,
+                ]
             )
         );
+        ticket.lottery = lottery_key;
+        TicketData::pack(ticket, &mut user2_ticket_acc.data).unwrap();
 
-        // Purchase tickets
-        user1_wallet_acc.lamports += sol_to_lamports(0.1);
-        do_process(
-            crate::instruction::purchase_ticket(
-                &program_id,
-                &user1_charity,
-                &user1_wallet,
-                &[11, 22, 33, 44, 51, 1],
-                &user1_ticket,
-                &holding_wallet,
-                &lottery_key,
-                &user1_lifetime_ticket_key,
-                &lifetime_ticket_owner_key,
-                &lifetime_ticket_mint_key,
-            )
-            .unwrap(),
-            vec![
-                &mut lottery_acc,
-                &mut user1_ticket_acc,
-                &mut user1_wallet_acc,
-                &mut holding_wallet_acc,
-                &mut user1_lifetime_ticket_acc,
-                &mut lifetime_ticket_owner_acc,
-                &mut lifetime_ticket_mint_acc,
-                &mut rent_sysvar_acc,
-                &mut system_acc,
-                &mut spl_token_acc,
-            ],
-        )
-        .unwrap();
-
-        let user2_charity = charity_1;
-        user2_wallet_acc.lamports += sol_to_lamports(0.1);
         do_process(
-            crate::instruction::purchase_ticket(
+            crate::instruction::settle_batch(
                 &program_id,
-                &user2_charity,
-                &user2_wallet,
-                &[2, 3, 4, 5, 66, 7],
-                &user2_ticket,
-                &holding_wallet,
                 &lottery_key,
-                &user2_lifetime_ticket_key,
-                &lifetime_ticket_owner_key,
-                &lifetime_ticket_mint_key,
+                &lottery_authority,
+                &[user2_ticket],
             )
             .unwrap(),
             vec![
                 &mut lottery_acc,
+                &mut lottery_authority_acc,
                 &mut user2_ticket_acc,
-                &mut user2_wallet_acc,
-                &mut holding_wallet_acc,
-                &mut user2_lifetime_ticket_acc,
-                &mut lifetime_ticket_owner_acc,
-                &mut lifetime_ticket_mint_acc,
-                &mut rent_sysvar_acc,
-                &mut system_acc,
-                &mut spl_token_acc,
             ],
         )
         .unwrap();
 
-        // Store winning numbers
-        do_process(
-            crate::instruction::store_winning_numbers(
-                &program_id,
-                &[2, 3, 4, 5, 66, 7],
-                &lottery_key,
-            )
-            .unwrap(),
-            vec![&mut lottery_acc],
-        )
-        .unwrap();
-
-        // BadCase: not enough users accounts
+        // BadCase: prize pool changed since sales close
+        let mut lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        lottery.prize_pool_amount += 1;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
         assert_eq!(
-            Err(LotteryError::InvalidParticipantsAccounts.into()),
+            Err(LotteryError::SalesSnapshotMismatch.into()),
             do_process(
-                crate::instruction::reward_winners(
+                crate::instruction::finalize_settlement(
                     &program_id,
                     &lottery_key,
+                    &lottery_authority,
                     &lottery_result_key,
                     &holding_wallet,
                     &rewards_wallet,
-                    
-                    &vec![(user2_ticket, user2_wallet)],
+                    false,
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
+                    &mut lottery_authority_acc,
                     &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
                     &mut rewards_wallet_acc,
-                    
                     &mut system_acc,
-                    &mut user2_ticket_acc,
-                    &mut user2_wallet_acc
                 ]
             )
         );
+        lottery.prize_pool_amount -= 1;
+        LotteryData::pack(lottery, &mut lottery_acc.data).unwrap();
 
-        // BadCase: Bad wallet user pk in ticket data
-        let user1_fake_wallet = Pubkey::new_unique();
-        let mut user1_fake_wallet_acc = SolanaAccount::default();
+        // BadCase: prize vault rent reserve is not a part of the prize pool
+        let vault_lamports = vault_acc.lamports;
+        vault_acc.lamports = lottery.prize_pool_amount + lottery.vault_rent_reserve - 1;
         assert_eq!(
-            Err(LotteryError::InvalidParticipantsAccounts.into()),
+            Err(ProgramError::InsufficientFunds),
             do_process(
-                crate::instruction::reward_winners(
+                crate::instruction::finalize_settlement(
                     &program_id,
                     &lottery_key,
+                    &lottery_authority,
                     &lottery_result_key,
                     &holding_wallet,
                     &rewards_wallet,
-                   
-                    &vec![
-                        (user1_ticket, user1_fake_wallet),
-                        (user2_ticket, user2_wallet)
-                    ],
+                    false,
                 )
                 .unwrap(),
                 vec![
                     &mut lottery_acc,
+                    &mut lottery_authority_acc,
                     &mut lottery_result_acc,
+                    &mut vault_acc,
                     &mut holding_wallet_acc,
                     &mut rewards_wallet_acc,
-                   
                     &mut system_acc,
-                    &mut user1_ticket_acc,
-                    &mut user1_fake_wallet_acc,
-                    &mut user2_ticket_acc,
-                    &mut user2_wallet_acc
                 ]
             )
         );
+        vault_acc.lamports = vault_lamports;
 
-        // BadCase: Bad sollotto reward account
-        let fake_sollotto_labs_wallet = Pubkey::new_unique();
+        do_process(
+            crate::instruction::finalize_settlement(
+                &program_id,
+                &lottery_key,
+                &lottery_authority,
+                &lottery_result_key,
+                &holding_wallet,
+                &rewards_wallet,
+                false,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut lottery_result_acc,
+                &mut vault_acc,
+                &mut holding_wallet_acc,
+                &mut rewards_wallet_acc,
+                &mut system_acc,
+            ],
+        )
+        .unwrap();
+
+        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
+        assert_eq!(lottery.status, LotteryStatus::Settled);
+        assert_eq!(lottery.settle_cursor, 2);
+        assert_eq!(lottery.tier_winners[..4], [0, 0, 0, 1]);
+        assert_eq!(lottery.tier_payouts[3], sol_to_lamports(0.17));
+        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.17));
+
+        let lottery_result =
+            LotteryResultData::unpack_unchecked(lottery_result_acc.data()).unwrap();
+        assert_eq!(lottery_result.lottery_id, lottery_id);
+        assert_eq!(lottery_result.winning_numbers, [2, 3, 4, 5, 66, 7]);
+        assert_eq!(lottery_result.game_matrix, test_game_matrix());
+        assert_eq!(lottery_result.draw_proof, lottery.draw_proof);
+        assert_eq!(lottery_result.draw_proof.fulfillment_slot, 77);
+        assert_eq!(lottery_result.ticket_ledger_hash, [9; 32]);
+        assert_eq!(lottery_result.seed_commitment, lottery.seed_commitment);
+    }
+
+    #[test]
+    fn test_update_sollotto_wallets() {
+        let program_id = id();
+        let lottery_id = 112233;
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
+        let mut lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let holding_wallet = Pubkey::new_unique();
+        let rewards_wallet = Pubkey::new_unique();
+        let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+
+        let new_holding_wallet = Pubkey::new_unique();
+        let new_rewards_wallet = Pubkey::new_unique();
+
+        // BadCase: Lottery is not initialized
         assert_eq!(
-            Err(LotteryError::InvalidSollottoAccount.into()),
+            Err(LotteryError::NotInitialized.into()),
             do_process(
-                crate::instruction::reward_winners(
+                crate::instruction::update_sollotto_wallets(
                     &program_id,
+                    &new_holding_wallet,
+                    &new_rewards_wallet,
                     &lottery_key,
-                    &lottery_result_key,
-                    &holding_wallet,
-                    &rewards_wallet,
-                   
-                    &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
+                    &lottery_authority,
                 )
                 .unwrap(),
-                vec![
-                    &mut lottery_acc,
-                    &mut lottery_result_acc,
-                    &mut holding_wallet_acc,
-                    &mut rewards_wallet_acc,
-                   
-                    &mut system_acc,
-                    &mut user1_ticket_acc,
-                    &mut user1_wallet_acc,
-                    &mut user2_ticket_acc,
-                    &mut user2_wallet_acc
-                ]
+                vec![&mut lottery_acc, &mut lottery_authority_acc]
             )
         );
 
-        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
-        assert_eq!(lottery.total_registrations, 2);
-        assert_eq!(lottery.prize_pool_amount, sol_to_lamports(0.2));
-        
-        assert_eq!(lottery.winning_numbers, [2, 3, 4, 5, 66, 7]);
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: Invalid lottery authority
+        let fake_authority = Pubkey::new_unique();
+        let mut fake_authority_acc = SolanaAccount::default();
+        assert_eq!(
+            Err(LotteryError::InvalidAuthority.into()),
+            do_process(
+                crate::instruction::update_sollotto_wallets(
+                    &program_id,
+                    &new_holding_wallet,
+                    &new_rewards_wallet,
+                    &lottery_key,
+                    &fake_authority,
+                )
+                .unwrap(),
+                vec![&mut lottery_acc, &mut fake_authority_acc]
+            )
+        );
 
-        // User2 wins the lottery
-        holding_wallet_acc.lamports += sol_to_lamports(10.0);
         do_process(
-            crate::instruction::reward_winners(
+            crate::instruction::update_sollotto_wallets(
                 &program_id,
-                &lottery_key,
-                &lottery_result_key,
-                &holding_wallet,
-                &rewards_wallet,
-               
-                &vec![(user1_ticket, user1_wallet), (user2_ticket, user2_wallet)],
+                &new_holding_wallet,
+                &new_rewards_wallet,
+                &lottery_key,
+                &lottery_authority,
             )
             .unwrap(),
-            vec![
-                &mut lottery_acc,
-                &mut lottery_result_acc,
-                &mut holding_wallet_acc,
-                &mut rewards_wallet_acc,
-               
-                &mut system_acc,
-                &mut user1_ticket_acc,
-                &mut user1_wallet_acc,
-                &mut user2_ticket_acc,
-                &mut user2_wallet_acc,
-            ],
+            vec![&mut lottery_acc, &mut lottery_authority_acc],
         )
         .unwrap();
 
-        // Check data
-        let lottery = LotteryData::unpack_unchecked(lottery_acc.data()).unwrap();
-        assert_eq!(lottery.is_initialized, false);
-        assert_eq!(lottery.is_finaled, false);
-        assert_eq!(lottery.total_registrations, 0);
-        assert_eq!(lottery.winning_numbers, [0, 0, 0, 0, 0, 0]);
-        assert_eq!(lottery.prize_pool_amount, 0);
-
-        let lottery_result =
-            LotteryResultData::unpack_unchecked(lottery_result_acc.data()).unwrap();
-        assert_eq!(lottery_result.lottery_id, lottery_id);
-        assert_eq!(lottery_result.winning_numbers, [2, 3, 4, 5, 66, 7]);
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.holding_wallet, new_holding_wallet);
+        assert_eq!(lottery.rewards_wallet, new_rewards_wallet);
     }
-
     #[test]
-    fn test_update_charity() {
+    fn test_update_ticket_price() {
         let program_id = id();
         let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
+        let (lottery_key, _) = find_lottery_address(&program_id, &lottery_authority, lottery_id);
         let mut lottery_acc = SolanaAccount::new(
             lottery_minimum_balance(),
             LotteryData::get_packed_len(),
             &program_id,
         );
         let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
+        let mut system_acc = SolanaAccount::default();
+        let mut vault_acc = SolanaAccount::default();
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_OPEN_TS - 1,
+            ..Default::default()
+        });
         let holding_wallet = Pubkey::new_unique();
         let rewards_wallet = Pubkey::new_unique();
         let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
+        let new_ticket_price = sol_to_lamports(0.01);
 
-        // BadCase: Lottery is not initialized
+        do_process(
+            crate::instruction::initialize_lottery(
+                &program_id,
+                lottery_id,
+                &LotteryConfig {
+                    holding_wallet,
+                    rewards_wallet,
+                    randomness_account,
+                    game_matrix,
+                    ticket_price_lamports: ticket_price,
+                    ..test_lottery_config()
+                },
+                &lottery_authority,
+            )
+            .unwrap(),
+            vec![
+                &mut lottery_acc,
+                &mut lottery_authority_acc,
+                &mut rent_sysvar_acc,
+                &mut system_acc,
+                &mut vault_acc,
+            ],
+        )
+        .unwrap();
+
+        // BadCase: zero ticket price
         assert_eq!(
-            Err(LotteryError::NotInitialized.into()),
+            Err(LotteryError::InvalidTicketPrice.into()),
             do_process(
-                crate::instruction::update_charity(
+                crate::instruction::update_ticket_price(
                     &program_id,
-                    
+                    0,
+                    0,
+                    &lottery_key,
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![&mut lottery_acc, &mut lottery_authority_acc, &mut clock_sysvar_acc]
+            )
+        );
+
+        // BadCase: USD ticket price of lottery without price feed
+        assert_eq!(
+            Err(LotteryError::InvalidPriceFeed.into()),
+            do_process(
+                crate::instruction::update_ticket_price(
+                    &program_id,
+                    0,
+                    500,
                     &lottery_key,
+                    &lottery_authority,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc]
+                vec![&mut lottery_acc, &mut lottery_authority_acc, &mut clock_sysvar_acc]
             )
         );
 
         do_process(
-            crate::instruction::initialize_lottery(
+            crate::instruction::update_ticket_price(
                 &program_id,
-                lottery_id,
-                
-                &holding_wallet,
-                &rewards_wallet,
-               
-                &randomness_account,
+                new_ticket_price,
+                0,
                 &lottery_key,
+                &lottery_authority,
             )
             .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
+            vec![&mut lottery_acc, &mut lottery_authority_acc, &mut clock_sysvar_acc],
         )
         .unwrap();
 
-        
-        
+        let lottery = LotteryData::unpack(&lottery_acc.data).unwrap();
+        assert_eq!(lottery.ticket_price_lamports, new_ticket_price);
 
-        
-             
+        // BadCase: ticket sales already opened
+        let mut clock_sysvar_acc = create_account_for_test(&Clock {
+            unix_timestamp: SALES_OPEN_TS,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(LotteryError::SalesStarted.into()),
+            do_process(
+                crate::instruction::update_ticket_price(
+                    &program_id,
+                    ticket_price,
+                    0,
+                    &lottery_key,
+                    &lottery_authority,
+                )
+                .unwrap(),
+                vec![&mut lottery_acc, &mut lottery_authority_acc, &mut clock_sysvar_acc]
+            )
+        );
     }
 
     #[test]
-    fn test_update_sollotto_wallets() {
-        let program_id = id();
-        let lottery_id = 112233;
-        let lottery_key = Pubkey::new_unique();
-        let mut lottery_acc = SolanaAccount::new(
-            lottery_minimum_balance(),
-            LotteryData::get_packed_len(),
-            &program_id,
+    fn test_usd_cents_to_lamports() {
+        // $1.00 at SOL/USD 100.00000000
+        assert_eq!(
+            usd_cents_to_lamports(100, 10_000_000_000, 8),
+            Some(sol_to_lamports(0.01))
+        );
+        // $5.00 at SOL/USD 3.00 rounds up
+        assert_eq!(usd_cents_to_lamports(500, 300, 2), Some(1_666_666_667));
+        assert_eq!(usd_cents_to_lamports(100, 0, 8), None);
+        assert_eq!(usd_cents_to_lamports(u64::MAX, 1, 0), None);
+    }
+
+    #[test]
+    fn test_draw_numbers() {
+        let game_matrix = test_game_matrix();
+        assert_eq!(draw_numbers(&GameMatrix::default(), &[1; 32]), None);
+        assert_eq!(
+            draw_numbers(&game_matrix, &[1; 32]),
+            draw_numbers(&game_matrix, &[1; 32])
+        );
+        assert_ne!(
+            draw_numbers(&game_matrix, &[1; 32]),
+            draw_numbers(&game_matrix, &[2; 32])
+        );
+
+        // Draws are valid, main balls and bonus balls are distinct and sorted
+        let draws = 20_000u32;
+        let mut main_counts = [0u32; 70];
+        let mut bonus_counts = [0u32; 30];
+        for seed in 0..draws {
+            let numbers = draw_numbers(&game_matrix, &seed.to_le_bytes()).unwrap();
+            assert!(game_matrix.is_valid_combination(&numbers));
+            assert!(numbers[..5].windows(2).all(|pair| pair[0] < pair[1]));
+            for number in numbers[..5].iter() {
+                main_counts[*number as usize] += 1;
+            }
+            bonus_counts[numbers[5] as usize] += 1;
+        }
+
+        // Every number is drawn close to its expected frequency
+        let expected_main = (draws * 5 / 69) as f64;
+        for count in main_counts[1..].iter() {
+            assert!((*count as f64 - expected_main).abs() < expected_main * 0.15);
+        }
+        let expected_bonus = (draws / 29) as f64;
+        for count in bonus_counts[1..].iter() {
+            assert!((*count as f64 - expected_bonus).abs() < expected_bonus * 0.15);
+        }
+
+        // Chi-squared of main balls is within 99.9% quantile for 68 degrees of freedom
+        let chi_squared: f64 = main_counts[1..]
+            .iter()
+            .map(|count| (*count as f64 - expected_main).powi(2) / expected_main)
+            .sum();
+        assert!(chi_squared < 111.0);
+
+        // Modulo bias would draw numbers up to 56 twice as often in 1/200 game
+        let game_matrix = GameMatrix {
+            main_count: 1,
+            main_range: 200,
+            bonus_count: 0,
+            bonus_range: 0,
+            kind: GameKind::Balls,
+        };
+        let mut counts = [0u32; 201];
+        for seed in 0..draws * 5 {
+            let numbers = draw_numbers(&game_matrix, &seed.to_le_bytes()).unwrap();
+            assert_eq!(numbers[1..], [0; 5]);
+            counts[numbers[0] as usize] += 1;
+        }
+        let low: u32 = counts[1..=56].iter().sum();
+        let high: u32 = counts[57..=112].iter().sum();
+        assert!((low as f64 / high as f64 - 1.0).abs() < 0.05);
+
+        // Every number of the full range is drawn
+        let game_matrix = GameMatrix {
+            main_count: 6,
+            main_range: 6,
+            bonus_count: 0,
+            bonus_range: 0,
+            kind: GameKind::Balls,
+        };
+        assert_eq!(draw_numbers(&game_matrix, &[3; 32]), Some([1, 2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn test_digits_game() {
+        let game_matrix = GameMatrix {
+            main_count: 3,
+            main_range: 10,
+            bonus_count: 0,
+            bonus_range: 0,
+            kind: GameKind::Digits,
+        };
+        assert!(game_matrix.is_valid());
+        assert!(game_matrix.is_valid_combination(&[0, 0, 0, 0, 0, 0]));
+        assert!(game_matrix.is_valid_combination(&[9, 0, 9, 0, 0, 0]));
+        assert!(!game_matrix.is_valid_combination(&[10, 0, 0, 0, 0, 0]));
+        assert!(!game_matrix.is_valid_combination(&[1, 2, 3, 4, 0, 0]));
+
+        let mut packed = vec![0; GameMatrix::get_packed_len()];
+        GameMatrix::pack(game_matrix, &mut packed).unwrap();
+        assert_eq!(GameMatrix::unpack_unchecked(&packed).unwrap(), game_matrix);
+        packed[4] = 2;
+        assert_eq!(
+            Err(ProgramError::InvalidAccountData),
+            GameMatrix::unpack_unchecked(&packed)
+        );
+
+        // Digits game has no bonus balls and at most ten digits
+        for invalid in [
+            GameMatrix {
+                bonus_count: 1,
+                bonus_range: 10,
+                ..game_matrix
+            },
+            GameMatrix {
+                main_range: 11,
+                ..game_matrix
+            },
+            GameMatrix {
+                main_count: 7,
+                ..game_matrix
+            },
+        ]
+        .iter()
+        {
+            assert!(!invalid.is_valid());
+            assert_eq!(draw_numbers(invalid, &[1; 32]), None);
+        }
+
+        // Draws are in range, digit 0 is drawn, digits repeat and are not sorted
+        let draws = 20_000u32;
+        let mut counts = [0u32; 10];
+        let mut repeated = false;
+        let mut unsorted = false;
+        for seed in 0..draws {
+            let numbers = draw_numbers(&game_matrix, &seed.to_le_bytes()).unwrap();
+            assert!(game_matrix.is_valid_combination(&numbers));
+            repeated |= numbers[0] == numbers[1];
+            unsorted |= numbers[0] > numbers[1];
+            for digit in numbers[..3].iter() {
+                counts[*digit as usize] += 1;
+            }
+        }
+        assert!(repeated && unsorted);
+        let expected = (draws * 3 / 10) as f64;
+        for count in counts.iter() {
+            assert!((*count as f64 - expected).abs() < expected * 0.1);
+        }
+
+        // Digits match only at the same position
+        assert_eq!(
+            count_matches(&game_matrix, &[1, 2, 3, 0, 0, 0], &[1, 2, 3, 0, 0, 0]),
+            (3, false)
+        );
+        assert_eq!(
+            count_matches(&game_matrix, &[3, 2, 1, 0, 0, 0], &[1, 2, 3, 0, 0, 0]),
+            (1, false)
+        );
+        assert_eq!(
+            count_matches(&game_matrix, &[7, 7, 7, 0, 0, 0], &[7, 0, 7, 0, 0, 0]),
+            (2, false)
+        );
+        assert_eq!(
+            count_matches(&game_matrix, &[0, 0, 0, 0, 0, 0], &[0, 0, 0, 0, 0, 0]),
+            (3, false)
+        );
+
+        // Assigned all zero quick pick is not pending
+        let ticket = TicketData {
+            quick_pick: true,
+            ..Default::default()
+        };
+        assert!(ticket.is_quick_pick_pending());
+        let ticket = TicketData {
+            quick_pick_assigned: true,
+            ..ticket
+        };
+        assert!(!ticket.is_quick_pick_pending());
+    }
+
+    #[test]
+    fn test_verify_draw() {
+        let game_matrix = test_game_matrix();
+        let randomness = [9u8; 32];
+        let mut lottery_result = LotteryResultData {
+            lottery_id: 1,
+            winning_numbers: draw_numbers(&game_matrix, &randomness).unwrap(),
+            game_matrix,
+            draw_proof: DrawProof::new(
+                DRAW_ALGORITHM_VERSION,
+                RandomnessMode::SwitchboardVrf,
+                Pubkey::default(),
+                10,
+                &randomness,
+            )
+            .unwrap(),
+            ticket_ledger_hash: [0; 32],
+            seed_commitment: [0; 32],
+        };
+        assert!(verify_draw(&lottery_result));
+
+        let mut packed = vec![0; LotteryResultData::get_packed_len()];
+        LotteryResultData::pack(lottery_result, &mut packed).unwrap();
+        assert!(verify_draw(&LotteryResultData::unpack_unchecked(&packed).unwrap()));
+
+        // Unknown algorithm version
+        lottery_result.draw_proof.algorithm_version = DRAW_ALGORITHM_VERSION + 1;
+        assert!(!verify_draw(&lottery_result));
+        lottery_result.draw_proof.algorithm_version = DRAW_ALGORITHM_VERSION;
+
+        // Tampered winning numbers
+        lottery_result.winning_numbers[0] ^= 1;
+        assert!(!verify_draw(&lottery_result));
+
+        // Randomness does not fit into the draw proof
+        assert_eq!(
+            DrawProof::new(
+                DRAW_ALGORITHM_VERSION,
+                RandomnessMode::SwitchboardVrf,
+                Pubkey::default(),
+                0,
+                &[0; 65]
+            ),
+            None
+        );
+
+        // Commit-reveal randomness is the committed seed mixed with the slot hash
+        let seed = [7u8; 32];
+        let slot_hash = [44u8; 32];
+        let randomness = hashv(&[&seed, &slot_hash]).to_bytes();
+        let mut draw_proof = DrawProof::new(
+            DRAW_ALGORITHM_VERSION,
+            RandomnessMode::CommitReveal,
+            solana_program::sysvar::slot_hashes::id(),
+            44,
+            &randomness,
+        )
+        .unwrap();
+        draw_proof.seed = seed;
+        draw_proof.slot_hash = slot_hash;
+        let mut lottery_result = LotteryResultData {
+            lottery_id: 1,
+            winning_numbers: draw_numbers(&game_matrix, &randomness).unwrap(),
+            game_matrix,
+            draw_proof,
+            ticket_ledger_hash: [0; 32],
+            seed_commitment: hash(&seed).to_bytes(),
+        };
+        assert!(verify_draw(&lottery_result));
+
+        // Seed does not match the seed commitment
+        lottery_result.seed_commitment = hash(&[8u8; 32]).to_bytes();
+        assert!(!verify_draw(&lottery_result));
+        lottery_result.seed_commitment = hash(&seed).to_bytes();
+
+        // Randomness is not derived from the seed and the slot hash
+        lottery_result.draw_proof.slot_hash = [45u8; 32];
+        assert!(!verify_draw(&lottery_result));
+    }
+
+    #[test]
+    fn test_lottery_config_pack() {
+        let config = LotteryConfig {
+            randomness_mode: RandomnessMode::CommitReveal,
+            seed_commitment: [7; 32],
+            ticket_price_usd_cents: 500,
+            price_feed: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            ..test_lottery_config()
+        };
+        let mut packed = vec![0; LotteryConfig::get_packed_len()];
+        LotteryConfig::pack(config, &mut packed).unwrap();
+        assert_eq!(LotteryConfig::unpack_unchecked(&packed).unwrap(), config);
+
+        let instruction = LotteryInstruction::InitLottery {
+            lottery_id: 7,
+            config,
+        };
+        let data = instruction.pack();
+        assert_eq!(data.len(), 1 + 4 + LotteryConfig::LEN);
+        assert_eq!(LotteryInstruction::unpack(&data).unwrap(), instruction);
+        assert_eq!(
+            Err(LotteryError::InvalidInstruction.into()),
+            LotteryInstruction::unpack(&data[..data.len() - 1])
+        );
+    }
+
+    #[test]
+    fn test_ticket_pack() {
+        let ticket = TicketData {
+            is_purchased: true,
+            charity: Pubkey::new_unique(),
+            user_wallet_pk: Pubkey::new_unique(),
+            ticket_number_arr: [1, 2, 3, 4, 5, 6],
+            quick_pick: true,
+            is_claimed: true,
+            ticket_index: 7,
+            lottery: Pubkey::new_unique(),
+            lottery_id: 8,
+            purchase_slot: 9,
+            price_paid: 10,
+            quick_pick_assigned: true,
+        };
+        let mut packed = vec![0; TicketData::get_packed_len()];
+        TicketData::pack(ticket, &mut packed).unwrap();
+        assert_eq!(TicketData::unpack_unchecked(&packed).unwrap(), ticket);
+
+        let mut lines = [[0u8; 6]; MAX_TICKET_BOOK_LINES];
+        lines[0] = [1, 2, 3, 4, 5, 6];
+        lines[MAX_TICKET_BOOK_LINES - 1] = [6, 5, 4, 3, 2, 1];
+        let ticket_book = TicketBookData {
+            is_purchased: true,
+            charity: Pubkey::new_unique(),
+            user_wallet_pk: Pubkey::new_unique(),
+            lines_count: MAX_TICKET_BOOK_LINES as u8,
+            lines,
+            is_claimed: true,
+            ticket_index: 7,
+            lottery: Pubkey::new_unique(),
+            lottery_id: 8,
+            purchase_slot: 9,
+            price_paid: 10,
+        };
+        let mut packed = vec![0; TicketBookData::get_packed_len()];
+        TicketBookData::pack(ticket_book, &mut packed).unwrap();
+        assert_eq!(TicketBookData::unpack_unchecked(&packed).unwrap(), ticket_book);
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let game_matrix = test_game_matrix();
+        let prize_table = test_prize_table();
+        let winning_numbers = [10, 20, 30, 40, 50, 15];
+        let matches_of = |ticket_number_arr: [u8; 6]| {
+            count_matches(&game_matrix, &ticket_number_arr, &winning_numbers)
+        };
+        let tier_of = |ticket_number_arr: [u8; 6]| {
+            let (main_matches, bonus_match) = matches_of(ticket_number_arr);
+            prize_table.tier_index(main_matches, bonus_match)
+        };
+
+        // Main balls match in any order, bonus ball only in its own position
+        assert_eq!(matches_of([50, 40, 30, 20, 10, 15]), (5, true));
+        assert_eq!(matches_of([10, 20, 30, 40, 50, 16]), (5, false));
+        assert_eq!(matches_of([15, 1, 2, 3, 4, 10]), (0, false));
+        assert_eq!(matches_of([10, 10, 10, 10, 10, 1]), (1, false));
+
+        // 5+1 jackpot and 5+0
+        assert_eq!(tier_of([40, 10, 50, 20, 30, 15]), Some(3));
+        assert_eq!(tier_of([40, 10, 50, 20, 30, 1]), Some(2));
+        // 4+1 and 4+0
+        assert_eq!(tier_of([40, 10, 50, 20, 31, 15]), Some(1));
+        assert_eq!(tier_of([40, 10, 50, 20, 31, 1]), Some(1));
+        // 3+1 and 3+0
+        assert_eq!(tier_of([40, 10, 50, 21, 31, 15]), Some(0));
+        assert_eq!(tier_of([1, 2, 10, 20, 30, 1]), Some(0));
+        // 2+1 and less win nothing
+        assert_eq!(tier_of([10, 20, 1, 2, 3, 15]), None);
+        assert_eq!(tier_of([1, 2, 3, 4, 5, 15]), None);
+        assert_eq!(tier_of([1, 2, 3, 4, 5, 6]), None);
+
+        // Game without bonus ball never matches bonus
+        let game_matrix = GameMatrix {
+            main_count: 6,
+            main_range: 49,
+            bonus_count: 0,
+            bonus_range: 0,
+            kind: GameKind::Balls,
+        };
+        assert_eq!(
+            count_matches(&game_matrix, &[6, 5, 4, 3, 2, 1], &[1, 2, 3, 4, 5, 6]),
+            (6, false)
+        );
+    }
+
+    #[test]
+    fn test_split_prize_pool() {
+        let prize_table = test_prize_table();
+        let tier_winners = |winners: &[u32]| {
+            let mut tier_winners = [0; MAX_PRIZE_TIERS];
+            tier_winners[..winners.len()].copy_from_slice(winners);
+            tier_winners
+        };
+        let tier_payouts = |payouts: &[u64]| {
+            let mut tier_payouts = [0; MAX_PRIZE_TIERS];
+            tier_payouts[..payouts.len()].copy_from_slice(payouts);
+            tier_payouts
+        };
+
+        assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR), Some(u64::MAX));
+        assert_eq!(bps_of(u64::MAX, BPS_DENOMINATOR + 1), None);
+        assert_eq!(bps_of(199, 500), Some(9));
+
+        // Tier 5 winner gets 5% of the prize pool, the rest is swept
+        assert_eq!(
+            split_prize_pool(sol_to_lamports(0.2), &prize_table, &tier_winners(&[0, 0, 1, 0])),
+            Some(PrizeSplit {
+                sollotto_reward: sol_to_lamports(0.01),
+                tier_payouts: tier_payouts(&[0, 0, sol_to_lamports(0.01), 0]),
+                reserved: sol_to_lamports(0.01),
+                sweep: sol_to_lamports(0.18),
+                dust: 0,
+            })
         );
+
+        // Division remainder of tier 4 pool is rolled over
+        assert_eq!(
+            split_prize_pool(1_000_000_007, &prize_table, &tier_winners(&[0, 3, 0, 1])),
+            Some(PrizeSplit {
+                sollotto_reward: 50_000_000,
+                tier_payouts: tier_payouts(&[0, 16_666_666, 0, 850_000_005]),
+                reserved: 900_000_003,
+                sweep: 50_000_002,
+                dust: 2,
+            })
+        );
+
+        // Fixed prizes are limited by the prize pool
+        assert_eq!(
+            split_prize_pool(sol_to_lamports(0.1), &prize_table, &tier_winners(&[5, 0, 0, 1])),
+            Some(PrizeSplit {
+                sollotto_reward: 5_000_000,
+                tier_payouts: tier_payouts(&[19_000_000, 0, 0, 0]),
+                reserved: 95_000_000,
+                sweep: 0,
+                dust: 0,
+            })
+        );
+
+        // Payouts never exceed the prize pool
+        for prize_pool in [0, 1, 9_999, sol_to_lamports(0.3), u64::MAX].iter() {
+            for winners in [[0, 0, 0, 0], [7, 3, 2, 1], [1000, 0, 13, 0], [u32::MAX; 4]].iter() {
+                let tier_winners = tier_winners(winners);
+                let split = split_prize_pool(*prize_pool, &prize_table, &tier_winners).unwrap();
+                let mut reserved = 0u128;
+                for (winners_count, tier_payout) in
+                    tier_winners.iter().zip(split.tier_payouts.iter())
+                {
+                    reserved += *winners_count as u128 * *tier_payout as u128;
+                }
+                assert_eq!(reserved, split.reserved as u128);
+                assert_eq!(
+                    split.sollotto_reward as u128
+                        + reserved
+                        + split.sweep as u128
+                        + split.dust as u128,
+                    *prize_pool as u128
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_prize_table_tiers() {
+        let game_matrix = test_game_matrix();
+        let prize_table = test_prize_table();
+        assert!(prize_table.is_valid(&game_matrix));
+        assert_eq!(prize_table.pool_share_bps(), Some(9_500));
+
+        assert_eq!(prize_table.tier_index(2, true), None);
+        assert_eq!(prize_table.tier_index(3, false), Some(0));
+        assert_eq!(prize_table.tier_index(3, true), Some(0));
+        assert_eq!(prize_table.tier_index(5, false), Some(2));
+        assert_eq!(prize_table.tier_index(5, true), Some(3));
+
+        // Tier is not reachable in the game matrix
+        let mut bad_prize_table = prize_table;
+        bad_prize_table.tiers[0].main_matches = 6;
+        assert!(!bad_prize_table.is_valid(&game_matrix));
+
+        // Duplicated tier
+        let mut bad_prize_table = prize_table;
+        bad_prize_table.tiers[3].bonus_match = false;
+        assert!(!bad_prize_table.is_valid(&game_matrix));
+
+        // Pool share exceeds 100%
+        let mut bad_prize_table = prize_table;
+        bad_prize_table.tiers[3].amount = BPS_DENOMINATOR + 1;
+        assert!(!bad_prize_table.is_valid(&game_matrix));
+
+        // Bonus tier without bonus balls
+        let game_matrix = GameMatrix {
+            main_count: 6,
+            main_range: 49,
+            bonus_count: 0,
+            bonus_range: 0,
+            kind: GameKind::Balls,
+        };
+        assert!(!prize_table.is_valid(&game_matrix));
+
+        assert!(!PrizeTable::default().is_valid(&game_matrix));
+        assert_eq!(PrizeTable::new(&[PrizeTier::default(); MAX_PRIZE_TIERS + 1]), None);
+    }
+
+    #[test]
+    fn test_rollover_prize_pool() {
+        let program_id = id();
+        let lottery_authority = Pubkey::new_unique();
+        let mut lottery_authority_acc = SolanaAccount::default();
         let mut rent_sysvar_acc = create_account_for_test(&Rent::default());
-       
+        let mut system_acc = SolanaAccount::default();
         let holding_wallet = Pubkey::new_unique();
         let rewards_wallet = Pubkey::new_unique();
         let randomness_account = Pubkey::new_unique();
+        let game_matrix = test_game_matrix();
+        let ticket_price = sol_to_lamports(0.1);
 
-        let new_holding_wallet = Pubkey::new_unique();
-        let new_rewards_wallet = rewards_wallet;
-    
+        let settled_lottery_id = 112233;
+        let (settled_lottery_key, _) =
+            find_lottery_address(&program_id, &lottery_authority, settled_lottery_id);
+        let mut settled_lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut settled_vault_acc = SolanaAccount::default();
+        let next_lottery_id = 112234;
+        let (next_lottery_key, _) =
+            find_lottery_address(&program_id, &lottery_authority, next_lottery_id);
+        let mut next_lottery_acc = SolanaAccount::new(
+            lottery_minimum_balance(),
+            LotteryData::get_packed_len(),
+            &program_id,
+        );
+        let mut next_vault_acc = SolanaAccount::default();
 
-        // BadCase: Lottery is not initialized
-        assert_eq!(
-            Err(LotteryError::NotInitialized.into()),
+        for (lottery_id, lottery_acc, vault_acc) in [
+            (
+                settled_lottery_id,
+                &mut settled_lottery_acc,
+                &mut settled_vault_acc,
+            ),
+            (next_lottery_id, &mut next_lottery_acc, &mut next_vault_acc),
+        ]
+        .iter_mut()
+        {
             do_process(
-                crate::instruction::update_sollotto_wallets(
+                crate::instruction::initialize_lottery(
                     &program_id,
-                    &new_holding_wallet,
-                    &new_rewards_wallet,
-                  
-                    &lottery_key,
+                    *lottery_id,
+                    &LotteryConfig {
+                        holding_wallet,
+                        rewards_wallet,
+                        randomness_account,
+                        game_matrix,
+                        ticket_price_lamports: ticket_price,
+                        ..test_lottery_config()
+                    },
+                    &lottery_authority,
                 )
                 .unwrap(),
-                vec![&mut lottery_acc]
+                vec![
+                    &mut **lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut rent_sysvar_acc,
+                    &mut system_acc,
+                    &mut **vault_acc,
+                ],
             )
-        );
+            .unwrap();
+        }
 
-        do_process(
-            crate::instruction::initialize_lottery(
-                &program_id,
-                lottery_id,
-               
-                &holding_wallet,
-                &rewards_wallet,
-               
-                &randomness_account,
-                &lottery_key,
+        // BadCase: lottery is not settled
+        assert_eq!(
+            Err(LotteryError::InvalidStatusTransition.into()),
+            do_process(
+                crate::instruction::rollover_prize_pool(
+                    &program_id,
+                    &settled_lottery_key,
+                    &lottery_authority,
+                    &next_lottery_key,
+                    false,
+                )
+                .unwrap(),
+                vec![
+                    &mut settled_lottery_acc,
+                    &mut lottery_authority_acc,
+                    &mut settled_vault_acc,
+                    &mut next_lottery_acc,
+                    &mut next_vault_acc,
+                    &mut system_acc,
+                ]
             )
-            .unwrap(),
-            vec![&mut lottery_acc, &mut rent_sysvar_acc],
-        )
-        .unwrap();
-
-        
         );
-        assert_eq!(lottery.sollotto_labs_wallet, sollotto_labs_wallet);
+
+        let mut lottery = LotteryData::unpack(&settled_lottery_acc.data).unwrap();
+        lottery.status = LotteryStatus::Settled;
+        lottery.rollover_amount = 2;
+        LotteryData::pack(lottery, &mut settled_lottery_acc.data).unwrap();
 
         do_process(
-            crate::instruction::update_sollotto_wallets(
+            crate::instruction::rollover_prize_pool(
                 &program_id,
-                &new_holding_wallet,
-                &new_rewards_wallet,
-               
-                &lottery_key,
+                &settled_lottery_key,
+                &lottery_authority,
+                &next_lottery_key,
+                false,
             )
             .unwrap(),
-            vec![&mut lottery_acc],
+            vec![
+                &mut settled_lottery_acc,
+                &mut lottery_authority_acc,
+                &mut settled_vault_acc,
+                &mut next_lottery_acc,
+                &mut next_vault_acc,
+                &mut system_acc,
+            ],
         )
         .unwrap();
-        );
+
+        let settled_lottery = LotteryData::unpack(&settled_lottery_acc.data).unwrap();
+        assert_eq!(settled_lottery.rollover_amount, 0);
+        let next_lottery = LotteryData::unpack(&next_lottery_acc.data).unwrap();
+        assert_eq!(next_lottery.prize_pool_amount, 2);
     }
 }
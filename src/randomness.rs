@@ -0,0 +1,178 @@
+//! Draw randomness providers
+use crate::{error::LotteryError, state::LotteryData};
+use solana_program::{
+    account_info::AccountInfo,
+    hash::{hash, hashv},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar,
+};
+use std::convert::TryInto;
+use switchboard_program::VrfAccount;
+
+/// Draw randomness with the account and the slot it comes from
+pub struct Randomness {
+    pub value: Vec<u8>,
+    pub account: Pubkey,
+    pub slot: u64,
+    /// Revealed seed of commit-reveal randomness, zero for other providers
+    pub seed: [u8; 32],
+    /// Slot hash mixed with commit-reveal seed, zero for other providers
+    pub slot_hash: [u8; 32],
+}
+
+/// Source of the randomness the winning numbers are drawn from
+pub trait RandomnessProvider {
+    /// Returns the draw randomness of the lottery
+    fn randomness(&self, lottery_data: &LotteryData) -> Result<Randomness, ProgramError>;
+}
+
+/// Switchboard VRF result of the lottery randomness account
+pub struct SwitchboardVrf<'a> {
+    pub vrf_account_info: &'a AccountInfo<'a>,
+}
+
+impl<'a> RandomnessProvider for SwitchboardVrf<'a> {
+    fn randomness(&self, lottery_data: &LotteryData) -> Result<Randomness, ProgramError> {
+        if *self.vrf_account_info.key != lottery_data.randomness_account {
+            msg!("Vrf account is not the lottery randomness account");
+            return Err(LotteryError::InvalidRandomnessAccount.into());
+        }
+
+        let round = vrf_round(self.vrf_account_info)?;
+        check_vrf_round(&round, lottery_data)?;
+        Ok(Randomness {
+            value: round.randomness,
+            account: *self.vrf_account_info.key,
+            slot: round.fulfillment_slot,
+            seed: [0; 32],
+            slot_hash: [0; 32],
+        })
+    }
+}
+
+/// Latest verified round of the Switchboard VRF account
+pub struct VrfRound {
+    /// Round counter, incremented by Switchboard on every fulfilled request
+    pub counter: u64,
+    /// Slot the round is fulfilled at
+    pub fulfillment_slot: u64,
+    pub randomness: Vec<u8>,
+}
+
+/// Checks that the Vrf round is fulfilled for the lottery randomness request:
+/// rounds known at sales close or at the request could be used to pick winning tickets
+pub fn check_vrf_round(round: &VrfRound, lottery_data: &LotteryData) -> Result<(), ProgramError> {
+    if round.counter <= lottery_data.close_vrf_counter
+        || round.counter <= lottery_data.request_vrf_counter
+    {
+        msg!(
+            "Vrf round {} is not fulfilled after randomness request round {}",
+            round.counter,
+            lottery_data.request_vrf_counter
+        );
+        return Err(LotteryError::StaleRandomness.into());
+    }
+    if round.fulfillment_slot < lottery_data.randomness_request_slot {
+        msg!(
+            "Vrf round is fulfilled at slot {} before randomness request slot {}",
+            round.fulfillment_slot,
+            lottery_data.randomness_request_slot
+        );
+        return Err(LotteryError::StaleRandomness.into());
+    }
+    Ok(())
+}
+
+/// Reads the latest verified round of the Vrf account
+pub fn vrf_round<'a>(vrf_account_info: &'a AccountInfo<'a>) -> Result<VrfRound, ProgramError> {
+    let vrf_account = VrfAccount::new(vrf_account_info)?;
+    let randomness = vrf_account.get_verified_randomness()?;
+    Ok(VrfRound {
+        counter: vrf_account.vrf_account_data.counter.unwrap_or_default(),
+        fulfillment_slot: vrf_account
+            .vrf_account_data
+            .fulfillment_slot
+            .unwrap_or_default(),
+        randomness,
+    })
+}
+
+/// Returns counter of the latest Vrf round, zero if the Vrf account has no data yet.
+/// Fails if the Vrf account data can not be parsed
+pub fn vrf_round_counter<'a>(vrf_account_info: &'a AccountInfo<'a>) -> Result<u64, ProgramError> {
+    if vrf_account_info.data_is_empty() {
+        return Ok(0);
+    }
+    let vrf_account = VrfAccount::new(vrf_account_info)?;
+    Ok(vrf_account.vrf_account_data.counter.unwrap_or_default())
+}
+
+/// Operator seed mixed with the hash of the first slot after sales close
+pub struct CommitReveal<'a, 'b> {
+    pub seed: [u8; 32],
+    pub slot_hashes_info: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> RandomnessProvider for CommitReveal<'a, 'b> {
+    fn randomness(&self, lottery_data: &LotteryData) -> Result<Randomness, ProgramError> {
+        if hash(&self.seed).to_bytes() != lottery_data.seed_commitment {
+            msg!("Seed does not match the seed commitment");
+            return Err(LotteryError::InvalidSeedReveal.into());
+        }
+
+        if !sysvar::slot_hashes::check_id(self.slot_hashes_info.key) {
+            msg!("Invalid SlotHashes sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        // Hash of the first slot after sales close is unknown to the operator at commit
+        // and can not be picked by delaying the reveal
+        let (slot, slot_hash) = first_slot_hash_after(
+            &self.slot_hashes_info.data.borrow(),
+            lottery_data.sales_close_slot,
+        )
+        .ok_or(LotteryError::SlotHashNotAvailable)?;
+
+        Ok(Randomness {
+            value: hashv(&[&self.seed, &slot_hash]).to_bytes().to_vec(),
+            account: *self.slot_hashes_info.key,
+            slot,
+            seed: self.seed,
+            slot_hash,
+        })
+    }
+}
+
+/// Finds the first slot after `slot` and its hash in SlotHashes sysvar `data`.
+/// Returns `None` if the sysvar does not cover `slot` anymore or has no later slot yet
+pub fn first_slot_hash_after(data: &[u8], slot: u64) -> Option<(u64, [u8; 32])> {
+    let len = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    let mut first_after = None;
+    // Entries are sorted from the newest slot to the oldest
+    for entry in data.get(8..)?.chunks_exact(40).take(len) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().ok()?);
+        if entry_slot <= slot {
+            return first_after;
+        }
+        first_after = Some((entry_slot, entry[8..].try_into().ok()?));
+    }
+    None
+}
+
+/// Fixed randomness set at lottery initialization, for offline draw tests only
+#[cfg(feature = "test-randomness")]
+pub struct Deterministic;
+
+#[cfg(feature = "test-randomness")]
+impl RandomnessProvider for Deterministic {
+    fn randomness(&self, lottery_data: &LotteryData) -> Result<Randomness, ProgramError> {
+        Ok(Randomness {
+            value: lottery_data.seed_commitment.to_vec(),
+            account: Pubkey::default(),
+            slot: lottery_data.sales_close_slot,
+            seed: [0; 32],
+            slot_hash: [0; 32],
+        })
+    }
+}
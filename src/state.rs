@@ -6,20 +6,679 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+/// Lottery lifecycle status
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LotteryStatus {
+    /// Tickets can be purchased
+    Open,
+    /// Ticket sales are closed, draw is not requested yet
+    SalesClosed,
+    /// Draw is requested, waiting for randomness
+    AwaitingRandomness,
+    /// Winning numbers are stored
+    Drawn,
+    /// Winners are being rewarded
+    Settling,
+    /// All winners are rewarded
+    Settled,
+    /// Lottery is cancelled
+    Cancelled,
+}
+
+impl Default for LotteryStatus {
+    fn default() -> Self {
+        LotteryStatus::Open
+    }
+}
+
+impl LotteryStatus {
+    /// Returns true if lottery is allowed to move from this status to `next`
+    pub fn can_transition_to(&self, next: LotteryStatus) -> bool {
+        use LotteryStatus::*;
+        matches!(
+            (*self, next),
+            (Open, SalesClosed)
+                | (Open, Cancelled)
+                | (SalesClosed, AwaitingRandomness)
+                | (SalesClosed, Drawn)
+                | (SalesClosed, Cancelled)
+                | (AwaitingRandomness, Drawn)
+                | (AwaitingRandomness, Cancelled)
+                | (Drawn, Settling)
+                | (Drawn, Settled)
+                | (Settling, Settled)
+        )
+    }
+}
+
+/// Source of the draw randomness
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RandomnessMode {
+    /// Switchboard VRF result of the lottery randomness account
+    SwitchboardVrf,
+    /// Operator seed committed at lottery initialization, revealed after sales close
+    /// and mixed with the hash of the first slot after sales close
+    CommitReveal,
+    /// Seed commitment is used as the draw randomness, for offline draw tests only
+    #[cfg(feature = "test-randomness")]
+    Deterministic,
+}
+
+impl Default for RandomnessMode {
+    fn default() -> Self {
+        RandomnessMode::SwitchboardVrf
+    }
+}
+
+/// Unpacks randomness mode from its packed byte
+fn unpack_randomness_mode(src: &[u8; 1]) -> Result<RandomnessMode, ProgramError> {
+    Ok(match src {
+        [0] => RandomnessMode::SwitchboardVrf,
+        [1] => RandomnessMode::CommitReveal,
+        #[cfg(feature = "test-randomness")]
+        [2] => RandomnessMode::Deterministic,
+        _ => return Err(ProgramError::InvalidAccountData),
+    })
+}
+
+/// Max count of numbers in one ticket combination
+pub const MAX_TICKET_NUMBERS: usize = 6;
+
+/// Max range of one digit of `Digits` game
+pub const MAX_DIGIT_RANGE: u8 = 10;
+
+/// How ticket numbers are picked and matched
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameKind {
+    /// Distinct balls matched in any order
+    Balls,
+    /// Digits with repeats matched by position, as in 3-digit games
+    Digits,
+}
+
+impl Default for GameKind {
+    fn default() -> Self {
+        GameKind::Balls
+    }
+}
+
+/// Ticket number combination layout: `main_count` main balls from 1 to `main_range`
+/// followed by `bonus_count` bonus balls from 1 to `bonus_range`, rest is zero.
+/// `Digits` game has `main_count` digits from 0 to `main_range - 1` and no bonus balls
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GameMatrix {
+    pub main_count: u8,
+    pub main_range: u8,
+    pub bonus_count: u8,
+    pub bonus_range: u8,
+    pub kind: GameKind,
+}
+
+impl GameMatrix {
+    /// Returns true if numbers of the matrix fit into ticket combination
+    pub fn is_valid(&self) -> bool {
+        match self.kind {
+            GameKind::Balls => {
+                self.main_count > 0
+                    && self.main_count as usize + self.bonus_count as usize <= MAX_TICKET_NUMBERS
+                    && self.main_range >= self.main_count
+                    && self.bonus_range >= self.bonus_count
+            }
+            GameKind::Digits => {
+                self.main_count > 0
+                    && self.main_count as usize <= MAX_TICKET_NUMBERS
+                    && self.main_range > 1
+                    && self.main_range <= MAX_DIGIT_RANGE
+                    && self.bonus_count == 0
+                    && self.bonus_range == 0
+            }
+        }
+    }
+
+    /// Returns true if number combination matches the matrix,
+    /// main balls and bonus balls must be distinct, digits may repeat
+    pub fn is_valid_combination(&self, numbers: &[u8; MAX_TICKET_NUMBERS]) -> bool {
+        let main_count = self.main_count as usize;
+        if self.kind == GameKind::Digits {
+            return numbers.iter().enumerate().all(|(i, number)| {
+                if i < main_count {
+                    *number < self.main_range
+                } else {
+                    *number == 0
+                }
+            });
+        }
+        let bonus_end = main_count + self.bonus_count as usize;
+        numbers.iter().enumerate().all(|(i, number)| {
+            if i < main_count {
+                *number >= 1 && *number <= self.main_range && !numbers[..i].contains(number)
+            } else if i < bonus_end {
+                *number >= 1
+                    && *number <= self.bonus_range
+                    && !numbers[main_count..i].contains(number)
+            } else {
+                *number == 0
+            }
+        })
+    }
+}
+
+impl Sealed for GameMatrix {}
+
+impl Pack for GameMatrix {
+    /// 1 + 1 + 1 + 1 + 1 = 5
+    const LEN: usize = 5;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 5];
+        let kind = match src[4] {
+            0 => GameKind::Balls,
+            1 => GameKind::Digits,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(GameMatrix {
+            main_count: src[0],
+            main_range: src[1],
+            bonus_count: src[2],
+            bonus_range: src[3],
+            kind,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 5];
+        *dst = [
+            self.main_count,
+            self.main_range,
+            self.bonus_count,
+            self.bonus_range,
+            self.kind as u8,
+        ];
+    }
+}
+
+/// Max count of tiers in the prize table
+pub const MAX_PRIZE_TIERS: usize = 9;
+
+/// How the prize of the tier is computed
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrizeKind {
+    /// Every winning line gets fixed `amount`
+    Fixed,
+    /// Winning lines share `amount` basis points of the prize pool
+    PoolShare,
+}
+
+impl Default for PrizeKind {
+    fn default() -> Self {
+        PrizeKind::Fixed
+    }
+}
+
+/// Prize tier of the lines matching `main_matches` main balls
+/// and the bonus ball if `bonus_match` is set
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrizeTier {
+    pub main_matches: u8,
+    pub bonus_match: bool,
+    pub kind: PrizeKind,
+    /// Prize of one line for `Fixed` tier, basis points of the prize pool for `PoolShare` tier
+    pub amount: u64,
+}
+
+impl Sealed for PrizeTier {}
+
+impl Pack for PrizeTier {
+    /// 1 + 1 + 1 + 8 = 11
+    const LEN: usize = 11;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 11];
+        let (main_matches, bonus_match, kind, amount) = array_refs![src, 1, 1, 1, 8];
+
+        let bonus_match = match bonus_match {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let kind = match kind {
+            [0] => PrizeKind::Fixed,
+            [1] => PrizeKind::PoolShare,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(PrizeTier {
+            main_matches: main_matches[0],
+            bonus_match,
+            kind,
+            amount: u64::from_le_bytes(*amount),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 11];
+        let (main_matches_dst, bonus_match_dst, kind_dst, amount_dst) =
+            mut_array_refs![dst, 1, 1, 1, 8];
+
+        main_matches_dst[0] = self.main_matches;
+        bonus_match_dst[0] = self.bonus_match as u8;
+        kind_dst[0] = self.kind as u8;
+        *amount_dst = self.amount.to_le_bytes();
+    }
+}
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Prize tiers of the lottery
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrizeTable {
+    pub tiers_count: u8,
+    pub tiers: [PrizeTier; MAX_PRIZE_TIERS],
+}
+
+impl PrizeTable {
+    /// Creates prize table of the given tiers, returns `None` if there are too many tiers
+    pub fn new(tiers: &[PrizeTier]) -> Option<Self> {
+        if tiers.len() > MAX_PRIZE_TIERS {
+            return None;
+        }
+        let mut prize_table = PrizeTable {
+            tiers_count: tiers.len() as u8,
+            ..Default::default()
+        };
+        prize_table.tiers[..tiers.len()].copy_from_slice(tiers);
+        Some(prize_table)
+    }
+
+    /// Returns tiers of the table
+    pub fn tiers(&self) -> &[PrizeTier] {
+        &self.tiers[..self.tiers_count as usize]
+    }
+
+    /// Returns sum of pool shares of all tiers in basis points
+    pub fn pool_share_bps(&self) -> Option<u64> {
+        self.tiers()
+            .iter()
+            .filter(|tier| tier.kind == PrizeKind::PoolShare)
+            .try_fold(0u64, |sum, tier| sum.checked_add(tier.amount))
+    }
+
+    /// Returns true if every tier is reachable in the game matrix, tiers are unique
+    /// and pool shares do not exceed 100%
+    pub fn is_valid(&self, game_matrix: &GameMatrix) -> bool {
+        let tiers = self.tiers();
+        !tiers.is_empty()
+            && tiers.iter().enumerate().all(|(i, tier)| {
+                tier.main_matches > 0
+                    && tier.main_matches <= game_matrix.main_count
+                    && (!tier.bonus_match || game_matrix.bonus_count > 0)
+                    && tier.amount > 0
+                    && (tier.kind != PrizeKind::PoolShare || tier.amount <= BPS_DENOMINATOR)
+                    && !tiers[..i].iter().any(|other| {
+                        other.main_matches == tier.main_matches
+                            && other.bonus_match == tier.bonus_match
+                    })
+            })
+    }
+
+    /// Returns index of the tier won by the line, tier requiring the bonus ball
+    /// is preferred if the bonus ball is matched
+    pub fn tier_index(&self, main_matches: u8, bonus_match: bool) -> Option<usize> {
+        let find = |bonus_match| {
+            self.tiers().iter().position(|tier| {
+                tier.main_matches == main_matches && tier.bonus_match == bonus_match
+            })
+        };
+        if bonus_match {
+            find(true).or_else(|| find(false))
+        } else {
+            find(false)
+        }
+    }
+}
+
+impl Sealed for PrizeTable {}
+
+impl Pack for PrizeTable {
+    /// 1 + 11 * 9 = 100
+    const LEN: usize = 100;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 100];
+        let (tiers_count, tiers_src) = array_refs![src, 1, 99];
+        if tiers_count[0] as usize > MAX_PRIZE_TIERS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut tiers = [PrizeTier::default(); MAX_PRIZE_TIERS];
+        for (tier, tier_src) in tiers.iter_mut().zip(tiers_src.chunks_exact(PrizeTier::LEN)) {
+            *tier = PrizeTier::unpack_from_slice(tier_src)?;
+        }
+
+        Ok(PrizeTable {
+            tiers_count: tiers_count[0],
+            tiers,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 100];
+        let (tiers_count_dst, tiers_dst) = mut_array_refs![dst, 1, 99];
+
+        tiers_count_dst[0] = self.tiers_count;
+        for (tier_dst, tier) in tiers_dst
+            .chunks_exact_mut(PrizeTier::LEN)
+            .zip(self.tiers.iter())
+        {
+            tier.pack_into_slice(tier_dst);
+        }
+    }
+}
+
+/// Lottery configuration set by `InitLottery`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LotteryConfig {
+    pub holding_wallet: Pubkey,
+    pub rewards_wallet: Pubkey,
+    /// Vrf account of `SwitchboardVrf` randomness mode
+    pub randomness_account: Pubkey,
+    pub randomness_mode: RandomnessMode,
+    /// Hash of the operator seed of `CommitReveal` randomness mode
+    pub seed_commitment: [u8; 32],
+    pub sales_open_ts: i64,
+    pub sales_close_ts: i64,
+    pub draw_after_ts: i64,
+    pub game_matrix: GameMatrix,
+    pub prize_table: PrizeTable,
+    /// Ticket price in lamports, in token base units for SPL token lottery
+    pub ticket_price_lamports: u64,
+    pub ticket_price_usd_cents: u64,
+    /// Chainlink SOL/USD price feed of USD ticket price
+    pub price_feed: Pubkey,
+    /// SPL token mint of SPL token lottery, default pubkey for SOL lottery
+    pub token_mint: Pubkey,
+}
+
+impl Sealed for LotteryConfig {}
+
+impl Pack for LotteryConfig {
+    /// 32 + 32 + 32 + 1 + 32 + 8 + 8 + 8 + 5 + 100 + 8 + 8 + 32 + 32 = 338
+    const LEN: usize = 338;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 338];
+        let (
+            holding_wallet,
+            rewards_wallet,
+            randomness_account,
+            randomness_mode,
+            seed_commitment,
+            sales_open_ts,
+            sales_close_ts,
+            draw_after_ts,
+            game_matrix,
+            prize_table,
+            ticket_price_lamports,
+            ticket_price_usd_cents,
+            price_feed,
+            token_mint,
+        ) = array_refs![src, 32, 32, 32, 1, 32, 8, 8, 8, 5, 100, 8, 8, 32, 32];
+
+        Ok(LotteryConfig {
+            holding_wallet: Pubkey::new_from_array(*holding_wallet),
+            rewards_wallet: Pubkey::new_from_array(*rewards_wallet),
+            randomness_account: Pubkey::new_from_array(*randomness_account),
+            randomness_mode: unpack_randomness_mode(randomness_mode)?,
+            seed_commitment: *seed_commitment,
+            sales_open_ts: i64::from_le_bytes(*sales_open_ts),
+            sales_close_ts: i64::from_le_bytes(*sales_close_ts),
+            draw_after_ts: i64::from_le_bytes(*draw_after_ts),
+            game_matrix: GameMatrix::unpack_from_slice(game_matrix)?,
+            prize_table: PrizeTable::unpack_from_slice(prize_table)?,
+            ticket_price_lamports: u64::from_le_bytes(*ticket_price_lamports),
+            ticket_price_usd_cents: u64::from_le_bytes(*ticket_price_usd_cents),
+            price_feed: Pubkey::new_from_array(*price_feed),
+            token_mint: Pubkey::new_from_array(*token_mint),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 338];
+        let (
+            holding_wallet_dst,
+            rewards_wallet_dst,
+            randomness_account_dst,
+            randomness_mode_dst,
+            seed_commitment_dst,
+            sales_open_ts_dst,
+            sales_close_ts_dst,
+            draw_after_ts_dst,
+            game_matrix_dst,
+            prize_table_dst,
+            ticket_price_lamports_dst,
+            ticket_price_usd_cents_dst,
+            price_feed_dst,
+            token_mint_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 1, 32, 8, 8, 8, 5, 100, 8, 8, 32, 32];
+
+        holding_wallet_dst.copy_from_slice(self.holding_wallet.as_ref());
+        rewards_wallet_dst.copy_from_slice(self.rewards_wallet.as_ref());
+        randomness_account_dst.copy_from_slice(self.randomness_account.as_ref());
+        randomness_mode_dst[0] = self.randomness_mode as u8;
+        *seed_commitment_dst = self.seed_commitment;
+        *sales_open_ts_dst = self.sales_open_ts.to_le_bytes();
+        *sales_close_ts_dst = self.sales_close_ts.to_le_bytes();
+        *draw_after_ts_dst = self.draw_after_ts.to_le_bytes();
+        self.game_matrix.pack_into_slice(game_matrix_dst);
+        self.prize_table.pack_into_slice(prize_table_dst);
+        *ticket_price_lamports_dst = self.ticket_price_lamports.to_le_bytes();
+        *ticket_price_usd_cents_dst = self.ticket_price_usd_cents.to_le_bytes();
+        price_feed_dst.copy_from_slice(self.price_feed.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+    }
+}
+
+/// Max length of the draw randomness kept in the draw proof
+pub const MAX_DRAW_RANDOMNESS_LEN: usize = 64;
+
+/// Randomness the winning numbers are drawn from and where it comes from
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawProof {
+    /// Version of the algorithm deriving winning numbers from the randomness
+    pub algorithm_version: u8,
+    pub randomness_mode: RandomnessMode,
+    /// Vrf account of the randomness, SlotHashes sysvar for commit-reveal randomness
+    pub randomness_account: Pubkey,
+    /// Slot the Vrf round is fulfilled at, slot of the hash mixed with commit-reveal seed
+    pub fulfillment_slot: u64,
+    pub randomness_len: u8,
+    pub randomness: [u8; MAX_DRAW_RANDOMNESS_LEN],
+    /// Revealed seed of commit-reveal randomness
+    pub seed: [u8; 32],
+    /// Slot hash mixed with commit-reveal seed
+    pub slot_hash: [u8; 32],
+}
+
+impl Default for DrawProof {
+    fn default() -> Self {
+        DrawProof {
+            algorithm_version: 0,
+            randomness_mode: RandomnessMode::default(),
+            randomness_account: Pubkey::default(),
+            fulfillment_slot: 0,
+            randomness_len: 0,
+            randomness: [0; MAX_DRAW_RANDOMNESS_LEN],
+            seed: [0; 32],
+            slot_hash: [0; 32],
+        }
+    }
+}
+
+impl DrawProof {
+    /// Creates draw proof, returns `None` if `randomness` is too long
+    pub fn new(
+        algorithm_version: u8,
+        randomness_mode: RandomnessMode,
+        randomness_account: Pubkey,
+        fulfillment_slot: u64,
+        randomness: &[u8],
+    ) -> Option<Self> {
+        if randomness.len() > MAX_DRAW_RANDOMNESS_LEN {
+            return None;
+        }
+        let mut proof = DrawProof {
+            algorithm_version,
+            randomness_mode,
+            randomness_account,
+            fulfillment_slot,
+            randomness_len: randomness.len() as u8,
+            ..DrawProof::default()
+        };
+        proof.randomness[..randomness.len()].copy_from_slice(randomness);
+        Some(proof)
+    }
+
+    /// Returns the draw randomness
+    pub fn randomness(&self) -> &[u8] {
+        &self.randomness[..self.randomness_len as usize]
+    }
+}
+
+impl Sealed for DrawProof {}
+
+impl Pack for DrawProof {
+    /// 1 + 1 + 32 + 8 + 1 + 64 + 32 + 32 = 171
+    const LEN: usize = 171;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 171];
+        let (
+            algorithm_version,
+            randomness_mode,
+            randomness_account,
+            fulfillment_slot,
+            randomness_len,
+            randomness,
+            seed,
+            slot_hash,
+        ) = array_refs![src, 1, 1, 32, 8, 1, 64, 32, 32];
+
+        if randomness_len[0] as usize > MAX_DRAW_RANDOMNESS_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(DrawProof {
+            algorithm_version: algorithm_version[0],
+            randomness_mode: unpack_randomness_mode(randomness_mode)?,
+            randomness_account: Pubkey::new_from_array(*randomness_account),
+            fulfillment_slot: u64::from_le_bytes(*fulfillment_slot),
+            randomness_len: randomness_len[0],
+            randomness: *randomness,
+            seed: *seed,
+            slot_hash: *slot_hash,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 171];
+        let (
+            algorithm_version_dst,
+            randomness_mode_dst,
+            randomness_account_dst,
+            fulfillment_slot_dst,
+            randomness_len_dst,
+            randomness_dst,
+            seed_dst,
+            slot_hash_dst,
+        ) = mut_array_refs![dst, 1, 1, 32, 8, 1, 64, 32, 32];
+
+        algorithm_version_dst[0] = self.algorithm_version;
+        randomness_mode_dst[0] = self.randomness_mode as u8;
+        randomness_account_dst.copy_from_slice(self.randomness_account.as_ref());
+        *fulfillment_slot_dst = self.fulfillment_slot.to_le_bytes();
+        randomness_len_dst[0] = self.randomness_len;
+        *randomness_dst = self.randomness;
+        *seed_dst = self.seed;
+        *slot_hash_dst = self.slot_hash;
+    }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct LotteryData {
     pub is_initialized: bool,
-    pub is_finaled: bool,
+    pub status: LotteryStatus,
     pub lottery_id: u32,
-   
     pub total_registrations: u32,
     pub winning_numbers: [u8; 6],
     pub prize_pool_amount: u64,
     pub holding_wallet: Pubkey,
     pub rewards_wallet: Pubkey,
-    
     pub randomness_account: Pubkey,
+    pub authority: Pubkey,
+    pub bump_seed: u8,
+    pub vault_bump_seed: u8,
+    pub game_matrix: GameMatrix,
+    pub ticket_price_lamports: u64,
+    pub ticket_price_usd_cents: u64,
+    pub price_feed: Pubkey,
+    /// SPL token mint of ticket price and prize pool, default pubkey for native SOL
+    pub token_mint: Pubkey,
+    pub token_vault_bump_seed: u8,
+    /// Hash of the draw randomness, seeds quick pick numbers
+    pub draw_seed: [u8; 32],
+    /// Prize of one winning line of every prize table tier, computed on settlement
+    pub tier_payouts: [u64; MAX_PRIZE_TIERS],
+    /// Count of winning lines of every prize table tier, counted on settlement
+    pub tier_winners: [u32; MAX_PRIZE_TIERS],
+    /// Registration index of the next line to be settled
+    pub settle_cursor: u32,
+    /// Undistributed dust of settled prize pool kept in prize vault for the next draw
+    pub rollover_amount: u64,
+    pub prize_table: PrizeTable,
+    /// Slot of ticket sales close
+    pub sales_close_slot: u64,
+    /// Round counter of the randomness account at sales close, draw requires a later round
+    pub close_vrf_counter: u64,
+    /// Slot of the last Switchboard VRF request
+    pub randomness_request_slot: u64,
+    /// Round counter of the randomness account at the last request, draw requires a later round
+    pub request_vrf_counter: u64,
+    pub randomness_mode: RandomnessMode,
+    /// Hash of the operator seed for `CommitReveal` randomness mode
+    pub seed_commitment: [u8; 32],
+    pub draw_proof: DrawProof,
+    /// Unix timestamp tickets can be purchased from
+    pub sales_open_ts: i64,
+    /// Unix timestamp tickets can be purchased until, exclusive
+    pub sales_close_ts: i64,
+    /// Unix timestamp winning numbers can be drawn from
+    pub draw_after_ts: i64,
+    /// Rolling hash of sold tickets, updated on every purchase
+    pub ticket_ledger_hash: [u8; 32],
+    /// Total registrations frozen at sales close
+    pub closed_registrations: u32,
+    /// Prize pool amount frozen at sales close
+    pub closed_prize_pool: u64,
+    /// Rent exempt balance of the prize vault, kept out of the prize pool
+    pub vault_rent_reserve: u64,
+}
+
+impl LotteryData {
+    /// Returns true if tickets are paid and prizes rewarded in SPL `token_mint`
+    pub fn is_token_lottery(&self) -> bool {
+        self.token_mint != Pubkey::default()
+    }
 }
 
 impl Sealed for LotteryData {}
@@ -32,24 +691,55 @@ impl IsInitialized for LotteryData {
 
 //pre-built
 impl Pack for LotteryData {
-    /// 1 + 1 + 4 + 32 + 32 + 32 + 32 + 4 + 4 + 4 + 4 + 4 + 6 + 8 + 32 + 32 + 32 + 32 + 32 = 296
-    const LEN: usize = 328;
+    /// 1 + 1 + 4 + 4 + 6 + 8 + 32 + 32 + 32 + 32 + 1 + 1 + 5 + 8 + 8 + 32 + 32 + 1 + 32 + 8 * 9
+    /// + 4 * 9 + 4 + 8 + 100 + 8 + 8 + 8 + 8 + 1 + 32 + 171 + 8 + 8 + 8 + 32 + 4 + 8 + 8 = 804
+    const LEN: usize = 804;
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 328];
+        let src = array_ref![src, 0, 804];
         let (
             is_initialized,
-            is_finaled,
+            status,
             lottery_id,
-            
             total_registrations,
             winning_numbers,
             prize_pool_amount,
             holding_wallet,
             rewards_wallet,
-            
             randomness_account,
-        ) = array_refs![src, 1, 1, 4, 32, 32, 32, 32, 4, 4, 4, 4, 4, 6, 8, 32, 32, 32, 32, 32];
+            authority,
+            bump_seed,
+            vault_bump_seed,
+            game_matrix,
+            ticket_price_lamports,
+            ticket_price_usd_cents,
+            price_feed,
+            token_mint,
+            token_vault_bump_seed,
+            draw_seed,
+            tier_payouts_src,
+            tier_winners_src,
+            settle_cursor,
+            rollover_amount,
+            prize_table,
+            sales_close_slot,
+            close_vrf_counter,
+            randomness_request_slot,
+            request_vrf_counter,
+            randomness_mode,
+            seed_commitment,
+            draw_proof,
+            sales_open_ts,
+            sales_close_ts,
+            draw_after_ts,
+            ticket_ledger_hash,
+            closed_registrations,
+            closed_prize_pool,
+            vault_rent_reserve,
+        ) = array_refs![
+            src, 1, 1, 4, 4, 6, 8, 32, 32, 32, 32, 1, 1, 5, 8, 8, 32, 32, 1, 32, 72, 36, 4, 8, 100,
+            8, 8, 8, 8, 1, 32, 171, 8, 8, 8, 32, 4, 8, 8
+        ];
 
         let is_initialized = match is_initialized {
             [0] => false,
@@ -57,56 +747,166 @@ impl Pack for LotteryData {
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
-        let is_finaled = match is_finaled {
-            [0] => false,
-            [1] => true,
+        let status = match status {
+            [0] => LotteryStatus::Open,
+            [1] => LotteryStatus::SalesClosed,
+            [2] => LotteryStatus::AwaitingRandomness,
+            [3] => LotteryStatus::Drawn,
+            [4] => LotteryStatus::Settling,
+            [5] => LotteryStatus::Settled,
+            [6] => LotteryStatus::Cancelled,
             _ => return Err(ProgramError::InvalidAccountData),
         };
 
+        let randomness_mode = unpack_randomness_mode(randomness_mode)?;
+
+        let mut tier_payouts = [0u64; MAX_PRIZE_TIERS];
+        for (tier_payout, src) in tier_payouts.iter_mut().zip(tier_payouts_src.chunks_exact(8)) {
+            *tier_payout = u64::from_le_bytes(*array_ref![src, 0, 8]);
+        }
+        let mut tier_winners = [0u32; MAX_PRIZE_TIERS];
+        for (tier_winner, src) in tier_winners.iter_mut().zip(tier_winners_src.chunks_exact(4)) {
+            *tier_winner = u32::from_le_bytes(*array_ref![src, 0, 4]);
+        }
+
         let result = LotteryData {
             is_initialized,
-            is_finaled,
+            status,
             lottery_id: u32::from_le_bytes(*lottery_id),
-            
             total_registrations: u32::from_le_bytes(*total_registrations),
             winning_numbers: *winning_numbers,
             prize_pool_amount: u64::from_le_bytes(*prize_pool_amount),
             holding_wallet: Pubkey::new_from_array(*holding_wallet),
             rewards_wallet: Pubkey::new_from_array(*rewards_wallet),
-            
             randomness_account: Pubkey::new_from_array(*randomness_account),
+            authority: Pubkey::new_from_array(*authority),
+            bump_seed: bump_seed[0],
+            vault_bump_seed: vault_bump_seed[0],
+            game_matrix: GameMatrix::unpack_from_slice(game_matrix)?,
+            ticket_price_lamports: u64::from_le_bytes(*ticket_price_lamports),
+            ticket_price_usd_cents: u64::from_le_bytes(*ticket_price_usd_cents),
+            price_feed: Pubkey::new_from_array(*price_feed),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            token_vault_bump_seed: token_vault_bump_seed[0],
+            draw_seed: *draw_seed,
+            tier_payouts,
+            tier_winners,
+            settle_cursor: u32::from_le_bytes(*settle_cursor),
+            rollover_amount: u64::from_le_bytes(*rollover_amount),
+            prize_table: PrizeTable::unpack_from_slice(prize_table)?,
+            sales_close_slot: u64::from_le_bytes(*sales_close_slot),
+            close_vrf_counter: u64::from_le_bytes(*close_vrf_counter),
+            randomness_request_slot: u64::from_le_bytes(*randomness_request_slot),
+            request_vrf_counter: u64::from_le_bytes(*request_vrf_counter),
+            randomness_mode,
+            seed_commitment: *seed_commitment,
+            draw_proof: DrawProof::unpack_from_slice(draw_proof)?,
+            sales_open_ts: i64::from_le_bytes(*sales_open_ts),
+            sales_close_ts: i64::from_le_bytes(*sales_close_ts),
+            draw_after_ts: i64::from_le_bytes(*draw_after_ts),
+            ticket_ledger_hash: *ticket_ledger_hash,
+            closed_registrations: u32::from_le_bytes(*closed_registrations),
+            closed_prize_pool: u64::from_le_bytes(*closed_prize_pool),
+            vault_rent_reserve: u64::from_le_bytes(*vault_rent_reserve),
         };
 
         Ok(result)
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 328];
+        let dst = array_mut_ref![dst, 0, 804];
         let (
             is_initialized_dst,
-            is_finaled_dst,
+            status_dst,
             lottery_id_dst,
-           
             total_registrations_dst,
             winning_numbers_dst,
             prize_pool_amount_dst,
             holding_wallet_dst,
             rewards_wallet_dst,
-           
             randomness_account_dst,
-        ) = mut_array_refs![dst, 1, 1, 4, 32, 32, 32, 32, 4, 4, 4, 4, 4, 6, 8, 32, 32, 32, 32, 32];
+            authority_dst,
+            bump_seed_dst,
+            vault_bump_seed_dst,
+            game_matrix_dst,
+            ticket_price_lamports_dst,
+            ticket_price_usd_cents_dst,
+            price_feed_dst,
+            token_mint_dst,
+            token_vault_bump_seed_dst,
+            draw_seed_dst,
+            tier_payouts_dst,
+            tier_winners_dst,
+            settle_cursor_dst,
+            rollover_amount_dst,
+            prize_table_dst,
+            sales_close_slot_dst,
+            close_vrf_counter_dst,
+            randomness_request_slot_dst,
+            request_vrf_counter_dst,
+            randomness_mode_dst,
+            seed_commitment_dst,
+            draw_proof_dst,
+            sales_open_ts_dst,
+            sales_close_ts_dst,
+            draw_after_ts_dst,
+            ticket_ledger_hash_dst,
+            closed_registrations_dst,
+            closed_prize_pool_dst,
+            vault_rent_reserve_dst,
+        ) = mut_array_refs![
+            dst, 1, 1, 4, 4, 6, 8, 32, 32, 32, 32, 1, 1, 5, 8, 8, 32, 32, 1, 32, 72, 36, 4, 8, 100,
+            8, 8, 8, 8, 1, 32, 171, 8, 8, 8, 32, 4, 8, 8
+        ];
 
         is_initialized_dst[0] = self.is_initialized as u8;
-        is_finaled_dst[0] = self.is_finaled as u8;
+        status_dst[0] = self.status as u8;
         *lottery_id_dst = self.lottery_id.to_le_bytes();
-        
         *total_registrations_dst = self.total_registrations.to_le_bytes();
         *winning_numbers_dst = self.winning_numbers;
         *prize_pool_amount_dst = self.prize_pool_amount.to_le_bytes();
         holding_wallet_dst.copy_from_slice(self.holding_wallet.as_ref());
         rewards_wallet_dst.copy_from_slice(self.rewards_wallet.as_ref());
-        
         randomness_account_dst.copy_from_slice(self.randomness_account.as_ref());
+        authority_dst.copy_from_slice(self.authority.as_ref());
+        bump_seed_dst[0] = self.bump_seed;
+        vault_bump_seed_dst[0] = self.vault_bump_seed;
+        self.game_matrix.pack_into_slice(game_matrix_dst);
+        *ticket_price_lamports_dst = self.ticket_price_lamports.to_le_bytes();
+        *ticket_price_usd_cents_dst = self.ticket_price_usd_cents.to_le_bytes();
+        price_feed_dst.copy_from_slice(self.price_feed.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        token_vault_bump_seed_dst[0] = self.token_vault_bump_seed;
+        *draw_seed_dst = self.draw_seed;
+        for (dst, tier_payout) in tier_payouts_dst
+            .chunks_exact_mut(8)
+            .zip(self.tier_payouts.iter())
+        {
+            dst.copy_from_slice(&tier_payout.to_le_bytes());
+        }
+        for (dst, tier_winner) in tier_winners_dst
+            .chunks_exact_mut(4)
+            .zip(self.tier_winners.iter())
+        {
+            dst.copy_from_slice(&tier_winner.to_le_bytes());
+        }
+        *settle_cursor_dst = self.settle_cursor.to_le_bytes();
+        *rollover_amount_dst = self.rollover_amount.to_le_bytes();
+        self.prize_table.pack_into_slice(prize_table_dst);
+        *sales_close_slot_dst = self.sales_close_slot.to_le_bytes();
+        *close_vrf_counter_dst = self.close_vrf_counter.to_le_bytes();
+        *randomness_request_slot_dst = self.randomness_request_slot.to_le_bytes();
+        *request_vrf_counter_dst = self.request_vrf_counter.to_le_bytes();
+        randomness_mode_dst[0] = self.randomness_mode as u8;
+        *seed_commitment_dst = self.seed_commitment;
+        self.draw_proof.pack_into_slice(draw_proof_dst);
+        *sales_open_ts_dst = self.sales_open_ts.to_le_bytes();
+        *sales_close_ts_dst = self.sales_close_ts.to_le_bytes();
+        *draw_after_ts_dst = self.draw_after_ts.to_le_bytes();
+        *ticket_ledger_hash_dst = self.ticket_ledger_hash;
+        *closed_registrations_dst = self.closed_registrations.to_le_bytes();
+        *closed_prize_pool_dst = self.closed_prize_pool.to_le_bytes();
+        *vault_rent_reserve_dst = self.vault_rent_reserve.to_le_bytes();
     }
 }
 
@@ -117,44 +917,241 @@ pub struct TicketData {
     pub charity: Pubkey,
     pub user_wallet_pk: Pubkey,
     pub ticket_number_arr: [u8; 6],
+    /// Numbers are assigned from the draw randomness after sales close
+    pub quick_pick: bool,
+    pub is_claimed: bool,
+    /// Registration index of the ticket line in the lottery
+    pub ticket_index: u32,
+    /// Lottery data account the ticket is purchased in
+    pub lottery: Pubkey,
+    /// Lottery round the ticket is purchased in
+    pub lottery_id: u32,
+    pub purchase_slot: u64,
+    /// Ticket price paid in lamports or SPL token amount
+    pub price_paid: u64,
+    /// Quick pick numbers are assigned, all zero digits are a valid pick
+    pub quick_pick_assigned: bool,
+}
+
+impl TicketData {
+    /// Returns true if quick pick numbers are not assigned yet
+    pub fn is_quick_pick_pending(&self) -> bool {
+        self.quick_pick && !self.quick_pick_assigned
+    }
 }
 
 impl Sealed for TicketData {}
 
 impl Pack for TicketData {
-    /// 1 + 32 + 32 + 1 * 6 = 70
-    const LEN: usize = 71;
+    /// 1 + 32 + 32 + 1 * 6 + 1 + 1 + 4 + 32 + 4 + 8 + 8 + 1 = 130
+    const LEN: usize = 130;
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 71];
-        let (is_purchased, charity, user_wallet_pk, ticket_number_arr) =
-            array_refs![src, 1, 32, 32, 6];
+        let src = array_ref![src, 0, 130];
+        let (
+            is_purchased,
+            charity,
+            user_wallet_pk,
+            ticket_number_arr,
+            quick_pick,
+            is_claimed,
+            ticket_index,
+            lottery,
+            lottery_id,
+            purchase_slot,
+            price_paid,
+            quick_pick_assigned,
+        ) = array_refs![src, 1, 32, 32, 6, 1, 1, 4, 32, 4, 8, 8, 1];
 
         let is_purchased = match is_purchased {
             [0] => false,
             [1] => true,
             _ => return Err(ProgramError::InvalidAccountData),
         };
+        let quick_pick = match quick_pick {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let is_claimed = match is_claimed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let quick_pick_assigned = match quick_pick_assigned {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
 
         let result = TicketData {
             is_purchased: is_purchased,
             charity: Pubkey::new_from_array(*charity),
             user_wallet_pk: Pubkey::new_from_array(*user_wallet_pk),
             ticket_number_arr: *ticket_number_arr,
+            quick_pick,
+            is_claimed,
+            ticket_index: u32::from_le_bytes(*ticket_index),
+            lottery: Pubkey::new_from_array(*lottery),
+            lottery_id: u32::from_le_bytes(*lottery_id),
+            purchase_slot: u64::from_le_bytes(*purchase_slot),
+            price_paid: u64::from_le_bytes(*price_paid),
+            quick_pick_assigned,
         };
 
         Ok(result)
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 71];
-        let (is_purchased_dst, charity_dst, user_wallet_pk_dst, ticket_number_arr_dst) =
-            mut_array_refs![dst, 1, 32, 32, 6];
+        let dst = array_mut_ref![dst, 0, 130];
+        let (
+            is_purchased_dst,
+            charity_dst,
+            user_wallet_pk_dst,
+            ticket_number_arr_dst,
+            quick_pick_dst,
+            is_claimed_dst,
+            ticket_index_dst,
+            lottery_dst,
+            lottery_id_dst,
+            purchase_slot_dst,
+            price_paid_dst,
+            quick_pick_assigned_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 6, 1, 1, 4, 32, 4, 8, 8, 1];
 
         is_purchased_dst[0] = self.is_purchased as u8;
         charity_dst.copy_from_slice(self.charity.as_ref());
         user_wallet_pk_dst.copy_from_slice(self.user_wallet_pk.as_ref());
         ticket_number_arr_dst.copy_from_slice(self.ticket_number_arr.as_ref());
+        quick_pick_dst[0] = self.quick_pick as u8;
+        is_claimed_dst[0] = self.is_claimed as u8;
+        *ticket_index_dst = self.ticket_index.to_le_bytes();
+        lottery_dst.copy_from_slice(self.lottery.as_ref());
+        *lottery_id_dst = self.lottery_id.to_le_bytes();
+        *purchase_slot_dst = self.purchase_slot.to_le_bytes();
+        *price_paid_dst = self.price_paid.to_le_bytes();
+        quick_pick_assigned_dst[0] = self.quick_pick_assigned as u8;
+    }
+}
+
+/// Max count of number combinations in one ticket book
+pub const MAX_TICKET_BOOK_LINES: usize = 20;
+
+/// Ticket book holds several number combinations (lines) of one purchase
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TicketBookData {
+    pub is_purchased: bool,
+    pub charity: Pubkey,
+    pub user_wallet_pk: Pubkey,
+    pub lines_count: u8,
+    pub lines: [[u8; 6]; MAX_TICKET_BOOK_LINES],
+    pub is_claimed: bool,
+    /// Registration index of the first line in the lottery
+    pub ticket_index: u32,
+    /// Lottery data account the ticket book is purchased in
+    pub lottery: Pubkey,
+    /// Lottery round the ticket book is purchased in
+    pub lottery_id: u32,
+    pub purchase_slot: u64,
+    /// Price of all lines paid in lamports or SPL token amount
+    pub price_paid: u64,
+}
+
+impl TicketBookData {
+    /// Returns purchased number combinations
+    pub fn purchased_lines(&self) -> &[[u8; 6]] {
+        &self.lines[..self.lines_count as usize]
+    }
+}
+
+impl Sealed for TicketBookData {}
+
+impl Pack for TicketBookData {
+    /// 1 + 32 + 32 + 1 + 6 * 20 + 1 + 4 + 32 + 4 + 8 + 8 = 243
+    const LEN: usize = 243;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 243];
+        let (
+            is_purchased,
+            charity,
+            user_wallet_pk,
+            lines_count,
+            lines_src,
+            is_claimed,
+            ticket_index,
+            lottery,
+            lottery_id,
+            purchase_slot,
+            price_paid,
+        ) = array_refs![src, 1, 32, 32, 1, 120, 1, 4, 32, 4, 8, 8];
+
+        let is_purchased = match is_purchased {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let is_claimed = match is_claimed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        if lines_count[0] as usize > MAX_TICKET_BOOK_LINES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut lines = [[0u8; 6]; MAX_TICKET_BOOK_LINES];
+        for (line, line_src) in lines.iter_mut().zip(lines_src.chunks_exact(6)) {
+            line.copy_from_slice(line_src);
+        }
+
+        let result = TicketBookData {
+            is_purchased,
+            charity: Pubkey::new_from_array(*charity),
+            user_wallet_pk: Pubkey::new_from_array(*user_wallet_pk),
+            lines_count: lines_count[0],
+            lines,
+            is_claimed,
+            ticket_index: u32::from_le_bytes(*ticket_index),
+            lottery: Pubkey::new_from_array(*lottery),
+            lottery_id: u32::from_le_bytes(*lottery_id),
+            purchase_slot: u64::from_le_bytes(*purchase_slot),
+            price_paid: u64::from_le_bytes(*price_paid),
+        };
+
+        Ok(result)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 243];
+        let (
+            is_purchased_dst,
+            charity_dst,
+            user_wallet_pk_dst,
+            lines_count_dst,
+            lines_dst,
+            is_claimed_dst,
+            ticket_index_dst,
+            lottery_dst,
+            lottery_id_dst,
+            purchase_slot_dst,
+            price_paid_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 1, 120, 1, 4, 32, 4, 8, 8];
+
+        is_purchased_dst[0] = self.is_purchased as u8;
+        charity_dst.copy_from_slice(self.charity.as_ref());
+        user_wallet_pk_dst.copy_from_slice(self.user_wallet_pk.as_ref());
+        lines_count_dst[0] = self.lines_count;
+        for (line_dst, line) in lines_dst.chunks_exact_mut(6).zip(self.lines.iter()) {
+            line_dst.copy_from_slice(line);
+        }
+        is_claimed_dst[0] = self.is_claimed as u8;
+        *ticket_index_dst = self.ticket_index.to_le_bytes();
+        lottery_dst.copy_from_slice(self.lottery.as_ref());
+        *lottery_id_dst = self.lottery_id.to_le_bytes();
+        *purchase_slot_dst = self.purchase_slot.to_le_bytes();
+        *price_paid_dst = self.price_paid.to_le_bytes();
     }
 }
 
@@ -163,31 +1160,60 @@ impl Pack for TicketData {
 pub struct LotteryResultData {
     pub lottery_id: u32,
     pub winning_numbers: [u8; 6],
+    pub game_matrix: GameMatrix,
+    /// Proof of the winning numbers draw for auditing
+    pub draw_proof: DrawProof,
+    /// Ticket ledger hash of all tickets sold, for auditing
+    pub ticket_ledger_hash: [u8; 32],
+    /// Commitment of the commit-reveal seed, for auditing
+    pub seed_commitment: [u8; 32],
 }
 
 impl Sealed for LotteryResultData {}
 
 impl Pack for LotteryResultData {
-    /// 4 + 6 = 10
-    const LEN: usize = 10;
+    /// 4 + 6 + 5 + 171 + 32 + 32 = 250
+    const LEN: usize = 250;
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 10];
-        let (lottery_id, winning_numbers) = array_refs![src, 4, 6];
+        let src = array_ref![src, 0, 250];
+        let (
+            lottery_id,
+            winning_numbers,
+            game_matrix,
+            draw_proof,
+            ticket_ledger_hash,
+            seed_commitment,
+        ) = array_refs![src, 4, 6, 5, 171, 32, 32];
 
         let result = LotteryResultData {
             lottery_id: u32::from_le_bytes(*lottery_id),
             winning_numbers: *winning_numbers,
+            game_matrix: GameMatrix::unpack_from_slice(game_matrix)?,
+            draw_proof: DrawProof::unpack_from_slice(draw_proof)?,
+            ticket_ledger_hash: *ticket_ledger_hash,
+            seed_commitment: *seed_commitment,
         };
 
         Ok(result)
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 10];
-        let (lottery_id_dst, winning_numbers_dst) = mut_array_refs![dst, 4, 6];
+        let dst = array_mut_ref![dst, 0, 250];
+        let (
+            lottery_id_dst,
+            winning_numbers_dst,
+            game_matrix_dst,
+            draw_proof_dst,
+            ticket_ledger_hash_dst,
+            seed_commitment_dst,
+        ) = mut_array_refs![dst, 4, 6, 5, 171, 32, 32];
 
         *lottery_id_dst = self.lottery_id.to_le_bytes();
         *winning_numbers_dst = self.winning_numbers;
+        self.game_matrix.pack_into_slice(game_matrix_dst);
+        self.draw_proof.pack_into_slice(draw_proof_dst);
+        *ticket_ledger_hash_dst = self.ticket_ledger_hash;
+        *seed_commitment_dst = self.seed_commitment;
     }
 }